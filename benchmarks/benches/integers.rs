@@ -0,0 +1,52 @@
+//! Benchmarks for encoding and decoding integer-heavy payloads.
+
+use bendy::{decoding::FromBencode, encoding::ToBencode};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const LIST_SIZE: i64 = 10_000;
+
+/// Integers of all magnitudes, from single digits to the full range of an `i64`
+fn mixed_integers() -> Vec<i64> {
+    (0..LIST_SIZE)
+        .map(|i| {
+            let magnitude = 10i64.saturating_pow((i % 19) as u32);
+            if i % 2 == 0 {
+                magnitude + i
+            } else {
+                -magnitude - i
+            }
+        })
+        .collect()
+}
+
+/// Small unsigned integers, like the lengths and indices common in real payloads
+fn small_integers() -> Vec<u32> {
+    (0..LIST_SIZE as u32).map(|i| i % 1000).collect()
+}
+
+fn encode(c: &mut Criterion) {
+    let mixed = mixed_integers();
+    let small = small_integers();
+
+    c.bench_function("encode mixed i64", |b| {
+        b.iter(|| black_box(&mixed).to_bencode().unwrap())
+    });
+    c.bench_function("encode small u32", |b| {
+        b.iter(|| black_box(&small).to_bencode().unwrap())
+    });
+}
+
+fn decode(c: &mut Criterion) {
+    let mixed = mixed_integers().to_bencode().unwrap();
+    let small = small_integers().to_bencode().unwrap();
+
+    c.bench_function("decode mixed i64", |b| {
+        b.iter(|| Vec::<i64>::from_bencode(black_box(&mixed)).unwrap())
+    });
+    c.bench_function("decode small u32", |b| {
+        b.iter(|| Vec::<u32>::from_bencode(black_box(&small)).unwrap())
+    });
+}
+
+criterion_group!(benches, encode, decode);
+criterion_main!(benches);
@@ -11,9 +11,10 @@
 //! ```
 //!
 //! Decoders have a depth limit to prevent resource exhaustion from hostile inputs. By default, it's
-//! set high enough for most structures that you'd encounter when prototyping, but for production
-//! use, not only may it not be enough, but the higher the depth limit, the more stack space an
-//! attacker can cause your program to use, so we recommend setting the bounds tightly:
+//! set to [`Decoder::DEFAULT_MAX_DEPTH`] (2048), which is high enough for most structures that
+//! you'd encounter when prototyping, but for production use, not only may it not be enough, but
+//! the higher the depth limit, the more stack space an attacker can cause your program to use, so
+//! we recommend setting the bounds tightly:
 //!
 //! ```
 //! # use bendy::decoding::{Decoder};
@@ -63,14 +64,26 @@
 //! # assert!(syntax_check(b"i18e"));
 //! ```
 
+#[cfg(feature = "arena")]
+mod arena;
 mod decoder;
 mod error;
+mod events;
 mod from_bencode;
 mod object;
+mod ordered_dict;
 
 pub use self::{
-    decoder::{Decoder, DictDecoder, ListDecoder, Tokens},
+    decoder::{
+        decode_all_lenient, decode_exact, raw_info_dict, take_object, DecodeStats, Decoder,
+        DictDecoder, ListDecoder, Tokens,
+    },
     error::{Error, ErrorKind, ResultExt},
-    from_bencode::FromBencode,
+    events::{Event, Events},
+    from_bencode::{FromBencode, FromBencodeTuple},
     object::Object,
+    ordered_dict::OrderedDict,
 };
+
+#[cfg(feature = "arena")]
+pub use self::arena::{decode_value_in, Arena, ArenaValue};
@@ -0,0 +1,133 @@
+use bumpalo::collections::Vec as ArenaVec;
+
+use crate::{
+    decoding::{Decoder, Error, ErrorKind, Object},
+    encoding::{self, SingleItemEncoder, ToBencode},
+};
+
+/// The arena that [`decode_value_in`] allocates from, re-exported from the `bumpalo` crate.
+pub use bumpalo::Bump as Arena;
+
+/// A bencoded value whose lists and dicts are allocated from an [`Arena`], and whose byte
+/// strings borrow from the decoded buffer.
+///
+/// Unlike a [`Value`](crate::value::Value), decoding an `ArenaValue` doesn't allocate on the
+/// heap per list or dict. Dict entries are kept in the order they were decoded in, which is
+/// sorted by key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArenaValue<'a> {
+    /// A byte string borrowed from the decoded buffer
+    Bytes(&'a [u8]),
+    /// A dictionary as a list of key/value pairs, sorted by key
+    Dict(&'a [(&'a [u8], ArenaValue<'a>)]),
+    /// A signed integer
+    Integer(i64),
+    /// A list of values
+    List(&'a [ArenaValue<'a>]),
+}
+
+/// Decode a single value from `buffer`, allocating its lists and dicts from `arena`.
+///
+/// This is meant for decoding many small messages with high throughput: the arena can be
+/// [reset](Arena::reset) between messages, so that its memory is reused instead of allocating
+/// and freeing every list and dict on the heap. Like [`FromBencode::from_bencode`], anything
+/// after the first value is ignored. The nesting depth is limited to
+/// [`Decoder::DEFAULT_MAX_DEPTH`].
+///
+/// [`FromBencode::from_bencode`]: crate::decoding::FromBencode::from_bencode
+///
+/// ```
+/// use bendy::decoding::{decode_value_in, Arena, ArenaValue};
+///
+/// let mut arena = Arena::new();
+/// for message in &[&b"li1ei2ee"[..], b"d3:fooi3ee"] {
+///     let value = decode_value_in(message, &arena).unwrap();
+///     match value {
+///         ArenaValue::List(_) | ArenaValue::Dict(_) => {},
+///         other => panic!("expected a list or dict, got {:?}", other),
+///     }
+///     arena.reset();
+/// }
+/// ```
+pub fn decode_value_in<'a>(buffer: &'a [u8], arena: &'a Arena) -> Result<ArenaValue<'a>, Error> {
+    let mut decoder = Decoder::new(buffer);
+    let value = match decoder.next_object()? {
+        Some(object) => decode_object(object, arena)?,
+        None => return Err(Error::from(ErrorKind::EmptyInput)),
+    };
+
+    Ok(value)
+}
+
+fn decode_object<'a>(object: Object<'_, 'a>, arena: &'a Arena) -> Result<ArenaValue<'a>, Error> {
+    match object {
+        Object::Bytes(bytes) => Ok(ArenaValue::Bytes(bytes)),
+        Object::Dict(mut decoder) => {
+            let mut dict = ArenaVec::new_in(arena);
+            while let Some((key, value)) = decoder.next_pair()? {
+                dict.push((key, decode_object(value, arena)?));
+            }
+            Ok(ArenaValue::Dict(dict.into_bump_slice()))
+        },
+        Object::Integer(text) => Ok(ArenaValue::Integer(text.parse()?)),
+        Object::List(mut decoder) => {
+            let mut list = ArenaVec::new_in(arena);
+            while let Some(object) = decoder.next_object()? {
+                list.push(decode_object(object, arena)?);
+            }
+            Ok(ArenaValue::List(list.into_bump_slice()))
+        },
+    }
+}
+
+impl<'a> ToBencode for ArenaValue<'a> {
+    // This leaves some room for external containers.
+    const MAX_DEPTH: usize = usize::max_value() / 4;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), encoding::Error> {
+        match self {
+            ArenaValue::Bytes(bytes) => encoder.emit_bytes(bytes),
+            ArenaValue::Dict(dict) => encoder.emit_dict(|mut e| {
+                for (key, value) in dict.iter() {
+                    e.emit_pair(key, value)?;
+                }
+                Ok(())
+            }),
+            ArenaValue::Integer(integer) => encoder.emit_int(*integer),
+            ArenaValue::List(list) => encoder.emit_unchecked_list(list.iter()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arena_values_should_round_trip() {
+        let arena = Arena::new();
+        let buffer = b"d3:barli1ei-2ee3:food1:a0:ee";
+        let value = decode_value_in(buffer, &arena).unwrap();
+
+        match value {
+            ArenaValue::Dict(&[(b"bar", ArenaValue::List(list)), (b"foo", _)]) => {
+                assert_eq!(list, [ArenaValue::Integer(1), ArenaValue::Integer(-2)]);
+            },
+            other => panic!("Unexpected value {:?}", other),
+        }
+        assert_eq!(value.to_bencode().unwrap(), &buffer[..]);
+    }
+
+    #[test]
+    fn arena_should_be_reusable() {
+        let mut arena = Arena::new();
+        for _ in 0..3 {
+            let value = decode_value_in(b"lli1eee", &arena).unwrap();
+            assert_eq!(value.to_bencode().unwrap(), b"lli1eee");
+            arena.reset();
+        }
+
+        assert!(decode_value_in(b"d1:bi1e1:ai2ee", &arena).is_err());
+        assert!(decode_value_in(b"", &arena).unwrap_err().is_empty_input());
+    }
+}
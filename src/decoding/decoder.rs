@@ -1,9 +1,10 @@
-use alloc::format;
-use core::str;
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use core::{fmt, ops::Range, str};
 
 use crate::{
-    decoding::{Error, Object},
+    decoding::{Error, ErrorKind, Events, FromBencode, Object},
     state_tracker::{StateTracker, StructureError, Token},
+    value::Value,
 };
 
 /// A bencode decoder
@@ -14,27 +15,204 @@ use crate::{
 pub struct Decoder<'a> {
     source: &'a [u8],
     offset: usize,
+    declared_length: Option<usize>,
+    max_string_length: usize,
+    max_total_string_bytes: usize,
+    total_string_bytes: usize,
+    allow_whitespace: bool,
+    stats: Option<DecodeStats>,
+    key_validator: Option<KeyValidator<'a>>,
     state: StateTracker<&'a [u8], Error>,
 }
 
+type KeyValidatorFn<'a> = dyn Fn(&[u8]) -> bool + Send + Sync + 'a;
+
+/// A callback deciding whether a dict key is acceptable
+struct KeyValidator<'a>(Box<KeyValidatorFn<'a>>);
+
+impl fmt::Debug for KeyValidator<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("KeyValidator")
+    }
+}
+
+/// Counts of the tokens seen by a [`Decoder`], collected when enabled with
+/// [`Decoder::with_stats()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecodeStats {
+    /// Number of integers
+    pub integers: usize,
+    /// Number of byte strings, including dict keys
+    pub byte_strings: usize,
+    /// Number of lists
+    pub lists: usize,
+    /// Number of dicts
+    pub dicts: usize,
+    /// Total length of the content of all byte strings
+    pub string_bytes: usize,
+    /// Deepest nesting level reached, with the same meaning as in [`Decoder::with_max_depth()`]
+    pub max_depth: usize,
+}
+
 impl<'ser> Decoder<'ser> {
-    /// Create a new decoder from the given byte array
+    /// The nesting depth a decoder accepts unless configured otherwise with
+    /// [`Decoder::with_max_depth()`]
+    pub const DEFAULT_MAX_DEPTH: usize = 2048;
+
+    /// The nesting depth accepted by [`Decoder::new_hardened()`]
+    pub const HARDENED_MAX_DEPTH: usize = 64;
+
+    /// The length of a single byte string accepted by [`Decoder::new_hardened()`], 1 MiB
+    pub const HARDENED_MAX_STRING_LENGTH: usize = 1 << 20;
+
+    /// The total length of all byte strings accepted by [`Decoder::new_hardened()`], 16 MiB
+    pub const HARDENED_MAX_TOTAL_STRING_BYTES: usize = 16 << 20;
+
+    /// Create a new decoder from the given byte array, limited to a nesting depth of
+    /// [`Decoder::DEFAULT_MAX_DEPTH`]
     pub fn new(buffer: &'ser [u8]) -> Self {
+        let mut state = StateTracker::new();
+        state.set_max_depth(Self::DEFAULT_MAX_DEPTH);
+
         Decoder {
             source: buffer,
             offset: 0,
-            state: StateTracker::new(),
+            declared_length: None,
+            max_string_length: usize::max_value(),
+            max_total_string_bytes: usize::max_value(),
+            total_string_bytes: 0,
+            allow_whitespace: false,
+            stats: None,
+            key_validator: None,
+            state,
         }
     }
 
+    /// Create a new decoder without any limit on the nesting depth.
+    ///
+    /// This is the same as `Decoder::new(buffer).with_max_depth(usize::max_value())`, but makes the
+    /// intent explicit. Hostile input can make such a decoder exhaust memory, so it should
+    /// only be used for trusted input.
+    pub fn new_unlimited(buffer: &'ser [u8]) -> Self {
+        Self::new(buffer).with_max_depth(usize::max_value())
+    }
+
+    /// Create a new decoder with conservative limits for untrusted input, e.g. for fuzzing or
+    /// for network-facing parsers:
+    ///
+    /// - a nesting depth of [`Decoder::HARDENED_MAX_DEPTH`] (64),
+    /// - byte strings of at most [`Decoder::HARDENED_MAX_STRING_LENGTH`] (1 MiB) each,
+    /// - and [`Decoder::HARDENED_MAX_TOTAL_STRING_BYTES`] (16 MiB) of byte strings in total.
+    ///
+    /// The number of items in lists and dicts is only bounded by the length of the input.
+    /// Each limit can still be adjusted with the corresponding `with_*` method.
+    ///
+    /// ```
+    /// # use bendy::decoding::Decoder;
+    /// #
+    /// let nested = [&[b'l'; 65][..], &[b'e'; 65][..]].concat();
+    ///
+    /// assert!(Decoder::new_hardened(&nested).tokens().any(|token| token.is_err()));
+    /// assert!(Decoder::new(&nested).tokens().all(|token| token.is_ok()));
+    /// ```
+    pub fn new_hardened(buffer: &'ser [u8]) -> Self {
+        Self::new(buffer)
+            .with_max_depth(Self::HARDENED_MAX_DEPTH)
+            .with_max_string_length(Self::HARDENED_MAX_STRING_LENGTH)
+            .with_max_total_string_bytes(Self::HARDENED_MAX_TOTAL_STRING_BYTES)
+    }
+
+    /// Create a new decoder that only reads the first `length` bytes of `buffer`, e.g. for a
+    /// frame of a protocol that declares the length of its payload.
+    ///
+    /// Unlike decoding `&buffer[..length]`, an object that doesn't end within those bytes is
+    /// reported as extending past the declared length rather than as an unexpected EOF. If
+    /// `buffer` is shorter than `length`, the input is truncated and reported as usual.
+    ///
+    /// ```
+    /// # use bendy::decoding::Decoder;
+    /// #
+    /// let decoder = Decoder::new_bounded(b"i1eli2ee", 5);
+    /// let error = decoder.tokens().find_map(Result::err).unwrap();
+    ///
+    /// assert!(error.to_string().contains("declared input length of 5 bytes"));
+    /// ```
+    pub fn new_bounded(buffer: &'ser [u8], length: usize) -> Self {
+        if length > buffer.len() {
+            return Self::new(buffer);
+        }
+
+        let mut decoder = Self::new(&buffer[..length]);
+        decoder.declared_length = Some(length);
+        decoder
+    }
+
     /// Set the maximum nesting depth of the decoder. An unlimited-depth decoder may be
-    /// created using `with_max_depth(<usize>::max_value())`, but be warned that this will likely
+    /// created using [`Decoder::new_unlimited()`], but be warned that this will likely
     /// exhaust memory if the nesting depth is too deep (even when reading raw tokens)
     pub fn with_max_depth(mut self, new_max_depth: usize) -> Self {
         self.state.set_max_depth(new_max_depth);
         self
     }
 
+    /// Set the maximum length of a single byte string. Longer byte strings are rejected before
+    /// their content is read. By default, the length of byte strings is unlimited.
+    pub fn with_max_string_length(mut self, new_max_string_length: usize) -> Self {
+        self.max_string_length = new_max_string_length;
+        self
+    }
+
+    /// Set the maximum total length of all byte strings read by the decoder. Unlike
+    /// [`Decoder::with_max_string_length()`], this also bounds the memory used by many byte
+    /// strings that are short enough by themselves once they are copied out of the input. By
+    /// default, the total length is unlimited.
+    pub fn with_max_total_string_bytes(mut self, new_max_total_string_bytes: usize) -> Self {
+        self.max_total_string_bytes = new_max_total_string_bytes;
+        self
+    }
+
+    /// Skip ASCII whitespace between tokens. Bencode has no notion of whitespace, so by default
+    /// any whitespace is rejected as an invalid token. This is meant for reading human-edited,
+    /// indented input and never touches the content of byte strings.
+    pub fn with_allow_whitespace(mut self, allow_whitespace: bool) -> Self {
+        self.allow_whitespace = allow_whitespace;
+        self
+    }
+
+    /// Check every dict key with the given validator, and fail with an error if it returns
+    /// `false`. This allows restricting keys to a known set or pattern in one place instead of
+    /// in every [`FromBencode`] implementation. The validator has to be `Send` and `Sync`, like
+    /// the rest of the decoder.
+    ///
+    /// ```
+    /// # use bendy::decoding::Decoder;
+    /// #
+    /// let mut decoder = Decoder::new(b"d3:f\xf6oi1ee").with_key_validator(|key| key.is_ascii());
+    /// let mut dict = decoder.next_object().unwrap().unwrap().try_into_dictionary().unwrap();
+    /// assert!(dict.next_pair().is_err());
+    /// ```
+    pub fn with_key_validator(
+        mut self,
+        validator: impl Fn(&[u8]) -> bool + Send + Sync + 'ser,
+    ) -> Self {
+        self.key_validator = Some(KeyValidator(Box::new(validator)));
+        self
+    }
+
+    /// Collect statistics about the decoded tokens, which can be read using [`Decoder::stats()`].
+    /// This is meant for profiling the shape of real traffic, e.g. to tune the limits of the
+    /// decoder.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(DecodeStats::default());
+        self
+    }
+
+    /// The statistics collected so far. All counts are zero unless collection was enabled with
+    /// [`Decoder::with_stats()`].
+    pub fn stats(&self) -> DecodeStats {
+        self.stats.unwrap_or_default()
+    }
+
     fn take_byte(&mut self) -> Option<u8> {
         if self.offset < self.source.len() {
             let ret = Some(self.source[self.offset]);
@@ -56,7 +234,7 @@ impl<'ser> Decoder<'ser> {
         }
     }
 
-    fn take_int(&mut self, expected_terminator: char) -> Result<&'ser str, StructureError> {
+    fn take_int(&mut self, expected_terminator: char) -> Result<&'ser str, Error> {
         enum State {
             Start,
             Sign,
@@ -64,6 +242,20 @@ impl<'ser> Decoder<'ser> {
             Digits,
         }
 
+        let number = if expected_terminator == ':' {
+            "byte string length"
+        } else {
+            "integer"
+        };
+        let illegal = |rule, offset| {
+            let error = StructureError::IllegalInteger {
+                number,
+                rule,
+                offset,
+            };
+            Err(Error::from(error).at_offset(offset))
+        };
+
         let mut curpos = self.offset;
         let mut state = State::Start;
         let mut success = false;
@@ -74,23 +266,47 @@ impl<'ser> Decoder<'ser> {
                     '-' => State::Sign,
                     '0' => State::Zero,
                     '0'..='9' => State::Digits,
-                    _ => return Err(StructureError::unexpected("'-' or '0'..'9'", c, curpos)),
+                    x if x == expected_terminator => {
+                        return illegal("at least one digit is required", curpos);
+                    },
+                    _ => {
+                        let error = StructureError::unexpected("'-' or '0'..'9'", c, curpos);
+                        return Err(Error::from(error).at_offset(curpos));
+                    },
                 },
-                State::Zero => {
-                    if c == expected_terminator {
+                State::Zero => match c {
+                    x if x == expected_terminator => {
                         success = true;
                         break;
-                    } else {
-                        return Err(StructureError::unexpected(
-                            &format!("{:?}", expected_terminator),
+                    },
+                    '0'..='9' => return illegal("leading zeros are not allowed", curpos),
+                    _ => {
+                        let error = StructureError::unexpected(
+                            format!("{:?}", expected_terminator),
                             c,
                             curpos,
-                        ));
-                    }
+                        );
+                        return Err(Error::from(error).at_offset(curpos));
+                    },
                 },
                 State::Sign => match c {
                     '1'..='9' => State::Digits,
-                    _ => return Err(StructureError::unexpected("'1'..'9'", c, curpos)),
+                    '0' => {
+                        let next = self.source.get(curpos + 1);
+                        return match next {
+                            Some(next) if next.is_ascii_digit() => {
+                                illegal("leading zeros are not allowed", curpos)
+                            },
+                            _ => illegal("negative zero is not allowed", curpos),
+                        };
+                    },
+                    x if x == expected_terminator => {
+                        return illegal("a minus sign must be followed by digits", curpos);
+                    },
+                    _ => {
+                        let error = StructureError::unexpected("'1'..'9'", c, curpos);
+                        return Err(Error::from(error).at_offset(curpos));
+                    },
                 },
                 State::Digits => match c {
                     '0'..='9' => state,
@@ -99,11 +315,12 @@ impl<'ser> Decoder<'ser> {
                         break;
                     },
                     _ => {
-                        return Err(StructureError::unexpected(
-                            &format!("{:?} or '0'..'9'", expected_terminator),
+                        let error = StructureError::unexpected(
+                            format!("{:?} or '0'..'9'", expected_terminator),
                             c,
                             curpos,
-                        ))
+                        );
+                        return Err(Error::from(error).at_offset(curpos));
                     },
                 },
             };
@@ -111,14 +328,15 @@ impl<'ser> Decoder<'ser> {
         }
 
         if !success {
-            return Err(StructureError::UnexpectedEof);
+            let error = self.truncated(StructureError::UnexpectedEof);
+            return Err(Error::from(error).at_offset(curpos));
         }
 
-        #[cfg(debug)]
-        let ival = istr::from_utf8(&self.source[self.offset..curpos])
+        #[cfg(debug_assertions)]
+        let ival = str::from_utf8(&self.source[self.offset..curpos])
             .expect("We've already examined every byte in the string");
 
-        #[cfg(not(debug))]
+        #[cfg(not(debug_assertions))]
         let ival = // Avoid a second UTF-8 check here
             unsafe { str::from_utf8_unchecked(&self.source[self.offset..curpos]) };
         self.offset = curpos + 1;
@@ -139,16 +357,45 @@ impl<'ser> Decoder<'ser> {
                 let len: usize = str::parse(ival).map_err(|_| StructureError::SyntaxError {
                     unexpected: format!("Invalid integer at offset {}", curpos),
                 })?;
-                Token::String(self.take_chunk(len).ok_or(StructureError::UnexpectedEof)?)
+                if len > self.max_string_length {
+                    return Err(Error::from(StructureError::StringTooLong {
+                        length: len,
+                        max_length: self.max_string_length,
+                    }));
+                }
+                let total_length = self.total_string_bytes.saturating_add(len);
+                if total_length > self.max_total_string_bytes {
+                    return Err(Error::from(StructureError::StringsTooLong {
+                        total_length,
+                        max_total_length: self.max_total_string_bytes,
+                    }));
+                }
+                self.total_string_bytes = total_length;
+                let remaining = self.source.len() - self.offset;
+                Token::String(self.take_chunk(len).ok_or_else(|| {
+                    // More input can complete a truncated string, unless its end lies beyond
+                    // the largest buffer there can be
+                    let error = match self.offset.checked_add(len) {
+                        Some(end) if end <= isize::max_value() as usize => {
+                            self.truncated(StructureError::UnexpectedEof)
+                        },
+                        _ => StructureError::StringLengthExceedsInput {
+                            length: len,
+                            remaining,
+                        },
+                    };
+                    Error::from(error).at_offset(self.source.len())
+                })?)
             },
             tok => {
-                return Err(Error::from(StructureError::SyntaxError {
+                let offset = self.offset - 1;
+                let error = StructureError::SyntaxError {
                     unexpected: format!(
                         "Invalid token starting with {:?} at offset {}",
-                        tok,
-                        self.offset - 1
+                        tok, offset
                     ),
-                }));
+                };
+                return Err(Error::from(error).at_offset(offset));
             },
         };
 
@@ -159,28 +406,154 @@ impl<'ser> Decoder<'ser> {
     fn next_token(&mut self) -> Result<Option<Token<'ser>>, Error> {
         self.state.check_error()?;
 
-        if self.offset == self.source.len() {
-            self.state.observe_eof()?;
+        if self.allow_whitespace {
+            while self
+                .source
+                .get(self.offset)
+                .map_or(false, u8::is_ascii_whitespace)
+            {
+                self.offset += 1;
+            }
+        }
+
+        let offset = self.offset;
+        if offset == self.source.len() {
+            if self.state.observe_eof().is_err() {
+                if let Some(declared_length) = self.declared_length {
+                    let error = StructureError::ExceedsDeclaredLength { declared_length };
+                    self.state.map_latched_err(|_| Error::from(error));
+                }
+                return Err(self.annotate_err(offset));
+            }
             return Ok(None);
         }
 
-        let tok_result = self.raw_next_token();
+        let expects_key = self.state.expects_key();
+        let tok_result = self.raw_next_token().map_err(|err| err.at_offset(offset));
         let tok = self.state.latch_err(tok_result)?;
-        self.state.observe_token(&tok)?;
+        self.state
+            .observe_token(&tok)
+            .map_err(|_| self.annotate_err(offset))?;
+
+        if let (true, Token::String(key), Some(KeyValidator(validator))) =
+            (expects_key, tok, &self.key_validator)
+        {
+            if !validator(key) {
+                let error = StructureError::InvalidKey {
+                    key: String::from_utf8_lossy(key).into_owned(),
+                };
+                self.state
+                    .latch_err(Err(Error::from(error).at_offset(offset)))?;
+            }
+        }
+
+        if let Some(stats) = self.stats.as_mut() {
+            match tok {
+                Token::Num(_) => stats.integers += 1,
+                Token::String(content) => {
+                    stats.byte_strings += 1;
+                    stats.string_bytes += content.len();
+                },
+                Token::List => stats.lists += 1,
+                Token::Dict => stats.dicts += 1,
+                Token::End => {},
+            }
+            stats.max_depth = stats.max_depth.max(self.state.depth());
+        }
+
         Ok(Some(tok))
     }
 
+    /// The error to report when the input ends in the middle of an object
+    fn truncated(&self, error: StructureError) -> StructureError {
+        match self.declared_length {
+            Some(declared_length) => StructureError::ExceedsDeclaredLength { declared_length },
+            None => error,
+        }
+    }
+
+    /// Attach the offset of the token that caused the latched structure error to that error
+    fn annotate_err(&mut self, offset: usize) -> Error {
+        self.state.map_latched_err(|err| err.at_offset(offset));
+        self.state
+            .check_error()
+            .expect_err("An error should have been latched")
+    }
+
     /// Iterate over the tokens in the input stream. This guarantees that the resulting stream
     /// of tokens constitutes a valid bencoded structure.
     pub fn tokens(self) -> Tokens<'ser> {
         Tokens(self)
     }
+
+    /// Get the bytes of the source buffer within `range`, or `None` if `range` is out of bounds,
+    /// e.g. to extract a span between two offsets reported by errors or noted while decoding.
+    ///
+    /// For a decoder created with [`Decoder::new_bounded()`], the source ends at the declared
+    /// length.
+    ///
+    /// ```
+    /// # use bendy::decoding::Decoder;
+    /// #
+    /// let decoder = Decoder::new(b"d3:fooi1ee");
+    ///
+    /// assert_eq!(decoder.source_slice(1..6), Some(&b"3:foo"[..]));
+    /// assert_eq!(decoder.source_slice(6..11), None);
+    /// ```
+    pub fn source_slice(&self, range: Range<usize>) -> Option<&'ser [u8]> {
+        self.source.get(range)
+    }
+
+    /// Take the decoder apart into its source buffer and the offset of the next unread byte,
+    /// e.g. to resume decoding later with `Decoder::new(&source[offset..])`.
+    ///
+    /// Only the position is preserved: a latched error, the configured limits and the nesting
+    /// state are discarded. Resuming is therefore only meaningful between top-level objects,
+    /// and an error that was latched before is not reported again by the new decoder.
+    ///
+    /// ```
+    /// # use bendy::decoding::Decoder;
+    /// #
+    /// let mut decoder = Decoder::new(b"i1ei2e");
+    /// decoder.next_object().unwrap();
+    ///
+    /// let (source, offset) = decoder.into_inner();
+    /// assert_eq!(&source[offset..], b"i2e");
+    /// ```
+    pub fn into_inner(self) -> (&'ser [u8], usize) {
+        (self.source, self.offset)
+    }
 }
 
 /// Iterator over the tokens in the input stream. This guarantees that the resulting stream
 /// of tokens constitutes a valid bencoded structure.
 pub struct Tokens<'a>(Decoder<'a>);
 
+impl<'a> Tokens<'a> {
+    /// Turn the raw token stream into a stream of structural [`Event`]s, which tell apart
+    /// dictionary keys from byte string values and the ends of lists from those of dictionaries.
+    ///
+    /// [`Event`]: crate::decoding::Event
+    pub fn events(self) -> Events<'a> {
+        Events::new(self)
+    }
+
+    /// The offset of the next unread byte of the input, which is where the next token starts.
+    ///
+    /// ```
+    /// # use bendy::decoding::Decoder;
+    /// #
+    /// let mut tokens = Decoder::new(b"li1e3:fooe").tokens();
+    /// tokens.next();
+    /// tokens.next();
+    ///
+    /// assert_eq!(tokens.offset(), 4);
+    /// ```
+    pub fn offset(&self) -> usize {
+        self.0.offset
+    }
+}
+
 impl<'a> Iterator for Tokens<'a> {
     type Item = Result<Token<'a>, Error>;
 
@@ -219,6 +592,195 @@ impl<'ser> Decoder<'ser> {
             Some(Num(s)) => Some(Object::Integer(s)),
         })
     }
+
+    /// Read the next object from the encoded stream and decode it into an owned [`Value`]
+    ///
+    /// Unlike the [`Object`] returned by [`next_object`](Decoder::next_object), the value doesn't
+    /// borrow the decoder, which is ready for the next object right away. At the end of the
+    /// input stream, this will return `Ok(None)`.
+    ///
+    /// ```
+    /// # use bendy::{decoding::Decoder, value::Value};
+    /// #
+    /// let mut decoder = Decoder::new(b"i1el3:fooe");
+    /// let first = decoder.next_object_owned().unwrap();
+    /// let second = decoder.next_object_owned().unwrap();
+    ///
+    /// assert_eq!(first, Some(Value::Integer(1)));
+    /// assert_eq!(second, Some(Value::List(vec![Value::Bytes(b"foo".to_vec().into())])));
+    /// assert_eq!(decoder.next_object_owned().unwrap(), None);
+    /// ```
+    pub fn next_object_owned(&mut self) -> Result<Option<Value<'static>>, Error> {
+        let value = match self.next_object()? {
+            Some(object) => Some(Value::decode_bencode_object(object)?),
+            None => None,
+        };
+
+        Ok(value)
+    }
+}
+
+/// Decode a single object from the front of `input` and advance `input` past it.
+///
+/// This is convenient when composing parsers over framed data, as the caller only has to hold
+/// on to a slice instead of a [`Decoder`] and its offset. If an error is encountered, `input` is
+/// left untouched.
+///
+/// ```
+/// # use bendy::{decoding::take_object, value::Value};
+/// #
+/// let mut input: &[u8] = b"i1e3:foo";
+///
+/// assert_eq!(take_object(&mut input).unwrap(), Value::Integer(1));
+/// assert_eq!(input, b"3:foo");
+/// ```
+pub fn take_object<'a>(input: &mut &'a [u8]) -> Result<Value<'a>, Error> {
+    let mut decoder = Decoder::new(input);
+    let value = match decoder.next_object()? {
+        Some(object) => Value::decode_bencode_object(object)?,
+        None => return Err(Error::from(ErrorKind::EmptyInput)),
+    };
+
+    *input = &input[decoder.offset..];
+    Ok(value)
+}
+
+/// Decode all top-level objects of `buffer`, e.g. a log of concatenated messages, without
+/// stopping at the first corrupt one.
+///
+/// Every successfully decoded object and every error is returned in input order. After an error,
+/// decoding resumes at the next position after the start of the failed object where a complete
+/// object can be decoded. This resynchronization is heuristic: a corrupt object may contain
+/// well-formed objects, such as the items of a list, which are then reported as top-level
+/// objects of their own. Resynchronizing takes time quadratic in the length of the damaged
+/// region, so this is meant for recovery tooling rather than for untrusted input.
+///
+/// Error offsets are relative to the start of `buffer`.
+///
+/// ```
+/// # use bendy::{decoding::decode_all_lenient, value::Value};
+/// #
+/// let results = decode_all_lenient(b"i1ei01ei2e");
+///
+/// assert_eq!(results.len(), 3);
+/// assert_eq!(results[0], Ok(Value::Integer(1)));
+/// assert_eq!(results[1].as_ref().unwrap_err().offset(), Some(5));
+/// assert_eq!(results[2], Ok(Value::Integer(2)));
+/// ```
+pub fn decode_all_lenient(buffer: &[u8]) -> Vec<Result<Value<'_>, Error>> {
+    let mut results = Vec::new();
+    let mut offset = 0;
+
+    while offset < buffer.len() {
+        match decode_value_at(buffer, offset) {
+            Ok((value, end)) => {
+                results.push(Ok(value));
+                offset = end;
+            },
+            Err(error) => {
+                results.push(Err(error));
+                let resumed = (offset + 1..buffer.len())
+                    .filter(|&start| match buffer[start] {
+                        b'd' | b'l' | b'i' | b'0'..=b'9' => true,
+                        _ => false,
+                    })
+                    .find_map(|start| decode_value_at(buffer, start).ok());
+                match resumed {
+                    Some((value, end)) => {
+                        results.push(Ok(value));
+                        offset = end;
+                    },
+                    None => break,
+                }
+            },
+        }
+    }
+
+    results
+}
+
+/// Decode a single value starting at `offset`, and return it with the offset right after it
+fn decode_value_at(buffer: &[u8], offset: usize) -> Result<(Value<'_>, usize), Error> {
+    let mut decoder = Decoder::new(buffer);
+    decoder.offset = offset;
+
+    let value = match decoder.next_object()? {
+        Some(object) => Value::decode_bencode_object(object)?,
+        None => return Err(Error::from(StructureError::UnexpectedEof)),
+    };
+
+    Ok((value, decoder.offset))
+}
+
+/// Decode a `T` from `buffer`, which must contain exactly one object.
+///
+/// Unlike [`FromBencode::from_bencode`], which ignores anything after the first object, this
+/// fails if any bytes remain after it. The depth limit is taken from
+/// [`FromBencode::EXPECTED_RECURSION_DEPTH`].
+///
+/// ```
+/// # use bendy::decoding::{decode_exact, FromBencode};
+/// #
+/// assert_eq!(decode_exact::<u32>(b"i1e").unwrap(), 1);
+/// assert!(decode_exact::<u32>(b"i1ei2e").is_err());
+/// assert_eq!(u32::from_bencode(b"i1ei2e").unwrap(), 1);
+/// ```
+pub fn decode_exact<T: FromBencode>(buffer: &[u8]) -> Result<T, Error> {
+    decode_exact_object(buffer).map_err(Error::in_type::<T>)
+}
+
+fn decode_exact_object<T: FromBencode>(buffer: &[u8]) -> Result<T, Error> {
+    let mut decoder = Decoder::new(buffer).with_max_depth(T::EXPECTED_RECURSION_DEPTH);
+    let value = match decoder.next_object()? {
+        Some(object) => T::decode_bencode_object(object)?,
+        None => return Err(Error::from(ErrorKind::EmptyInput)),
+    };
+
+    // This also surfaces errors in parts of the object that `T` didn't look at
+    let end = decoder.offset;
+    if decoder.next_object()?.is_some() {
+        return Err(Error::from(StructureError::TrailingData).at_offset(end));
+    }
+
+    Ok(value)
+}
+
+/// Get the raw bytes of the `info` dict of a BitTorrent metainfo file.
+///
+/// A torrent's info-hash is the SHA-1 (or SHA-256 for v2 torrents) of the `info` dict exactly as
+/// it appears in the file. Re-encoding a decoded dict can change those bytes, e.g. if it
+/// contained fields the decoding type doesn't know about, so the hash should be computed over
+/// the slice returned from here instead. The whole metainfo dict is validated.
+///
+/// ```
+/// # use bendy::decoding::raw_info_dict;
+/// #
+/// let torrent = b"d8:announce3:url4:infod6:lengthi3e4:name3:foo12:piece lengthi16384eee";
+///
+/// assert_eq!(
+///     raw_info_dict(torrent).unwrap(),
+///     &b"d6:lengthi3e4:name3:foo12:piece lengthi16384ee"[..]
+/// );
+/// ```
+pub fn raw_info_dict(torrent: &[u8]) -> Result<&[u8], Error> {
+    let mut decoder = Decoder::new(torrent);
+    let mut dict = match decoder.next_object()? {
+        Some(object) => object.try_into_dictionary()?,
+        None => return Err(Error::from(ErrorKind::EmptyInput)),
+    };
+
+    let mut info = None;
+    while let Some((key, value)) = dict.next_pair()? {
+        if key == b"info" {
+            let raw = value
+                .try_into_dictionary()
+                .and_then(DictDecoder::into_raw)
+                .map_err(|err| err.context("info"))?;
+            info = Some(raw);
+        }
+    }
+
+    info.ok_or_else(|| Error::missing_field("info"))
 }
 
 /// A dictionary read from the input stream
@@ -229,6 +791,19 @@ pub struct DictDecoder<'obj, 'ser: 'obj> {
     start_point: usize,
 }
 
+/// A dict key, the span of its encoding and its value
+type SpannedPair<'obj, 'ser> = (&'ser [u8], Range<usize>, Object<'obj, 'ser>);
+
+/// The number of decimal digits of `number`
+fn decimal_digits(mut number: usize) -> usize {
+    let mut digits = 1;
+    while number >= 10 {
+        number /= 10;
+        digits += 1;
+    }
+    digits
+}
+
 /// A list read from the input stream
 #[derive(Debug)]
 pub struct ListDecoder<'obj, 'ser: 'obj> {
@@ -252,6 +827,31 @@ impl<'obj, 'ser: 'obj> DictDecoder<'obj, 'ser> {
     pub fn next_pair<'item>(
         &'item mut self,
     ) -> Result<Option<(&'ser [u8], Object<'item, 'ser>)>, Error> {
+        let pair = self.next_pair_spanned()?;
+        Ok(pair.map(|(key, _, value)| (key, value)))
+    }
+
+    /// Parse the next key/value pair from the dictionary like [`DictDecoder::next_pair()`], and
+    /// also return the span of the encoded key in the source, including its length prefix. This
+    /// allows a key to be re-emitted byte for byte.
+    ///
+    /// ```
+    /// # use bendy::decoding::{Decoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let source = b"d3:fooi1ee";
+    /// let mut decoder = Decoder::new(source);
+    /// let mut dict = decoder.next_object()?.unwrap().try_into_dictionary()?;
+    ///
+    /// let (key, span, _value) = dict.next_pair_spanned()?.unwrap();
+    /// assert_eq!(key, b"foo");
+    /// assert_eq!(&source[span], b"3:foo");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn next_pair_spanned<'item>(
+        &'item mut self,
+    ) -> Result<Option<SpannedPair<'item, 'ser>>, Error> {
         if self.finished {
             return Ok(None);
         }
@@ -260,9 +860,13 @@ impl<'obj, 'ser: 'obj> DictDecoder<'obj, 'ser> {
         let key = self.decoder.next_object()?.map(Object::into_token);
 
         if let Some(Token::String(k)) = key {
+            // The key is canonical, so its encoding ends right here and has a length prefix of
+            // exactly as many digits as needed
+            let end = self.decoder.offset;
+            let span = end - k.len() - 1 - decimal_digits(k.len())..end;
             // This unwrap should be safe because None would produce an error here
             let v = self.decoder.next_object()?.unwrap();
-            Ok(Some((k, v)))
+            Ok(Some((k, span, v)))
         } else {
             // We can't have gotten anything but a string, as anything else would be
             // a state error
@@ -271,6 +875,36 @@ impl<'obj, 'ser: 'obj> DictDecoder<'obj, 'ser> {
         }
     }
 
+    /// Parse the next key/value pair from the dictionary and decode the value as a `T`. Returns
+    /// `Ok(None)` at the end of the dictionary. If the value can't be decoded, the key is added
+    /// to the context of the error.
+    ///
+    /// ```
+    /// # use bendy::decoding::{Decoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut decoder = Decoder::new(b"d1:ai1e1:bi2ee");
+    /// let mut dict = decoder.next_object()?.unwrap().try_into_dictionary()?;
+    ///
+    /// let mut sum = 0;
+    /// while let Some((_key, value)) = dict.next_pair_as::<u32>()? {
+    ///     sum += value;
+    /// }
+    /// assert_eq!(sum, 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn next_pair_as<T: FromBencode>(&mut self) -> Result<Option<(&'ser [u8], T)>, Error> {
+        match self.next_pair()? {
+            Some((key, value)) => {
+                let value = T::decode_bencode_object(value)
+                    .map_err(|err| err.context(String::from_utf8_lossy(key)))?;
+                Ok(Some((key, value)))
+            },
+            None => Ok(None),
+        }
+    }
+
     /// Consume (and validate the structure of) the rest of the items from the
     /// dictionary. This method should be used to check for encoding errors if
     /// [`DictDecoder::next_pair`] is not called until it returns `Ok(None)`.
@@ -319,6 +953,28 @@ impl<'obj, 'ser: 'obj> ListDecoder<'obj, 'ser> {
         Ok(item)
     }
 
+    /// Get the next item from the list and decode it as a `T`. Returns `Ok(None)` at the end of
+    /// the list. This is convenient for lists with a positional schema:
+    ///
+    /// ```
+    /// # use bendy::decoding::{Decoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut decoder = Decoder::new(b"li42e5:helloe");
+    /// let mut list = decoder.next_object()?.unwrap().try_into_list()?;
+    ///
+    /// assert_eq!(list.next_as::<u32>()?, Some(42));
+    /// assert_eq!(list.next_as::<String>()?, Some("hello".to_string()));
+    /// assert_eq!(list.next_as::<u32>()?, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn next_as<T: FromBencode>(&mut self) -> Result<Option<T>, Error> {
+        self.next_object()?
+            .map(T::decode_bencode_object)
+            .transpose()
+    }
+
     /// Consume (and validate the structure of) the rest of the items from the
     /// list. This method should be used to check for encoding errors if
     /// [`ListDecoder::next_object`] is not called until it returns [`Ok(())`].
@@ -348,17 +1004,18 @@ impl<'obj, 'ser: 'obj> Drop for ListDecoder<'obj, 'ser> {
 #[cfg(test)]
 mod test {
 
+    use alloc::collections::BTreeMap;
     #[cfg(not(feature = "std"))]
-    use alloc::{vec, vec::Vec};
+    use alloc::{string::ToString, vec, vec::Vec};
     use core::iter;
 
     use regex;
 
     use super::*;
 
-    static SIMPLE_MSG: &'static [u8] = b"d3:bari1e3:fooli2ei3eee";
+    static SIMPLE_MSG: &[u8] = b"d3:bari1e3:fooli2ei3eee";
 
-    fn decode_tokens(msg: &[u8]) -> Vec<Token> {
+    fn decode_tokens(msg: &[u8]) -> Vec<Token<'_>> {
         let tokens: Vec<Result<Token, Error>> = Decoder::new(msg).tokens().collect();
         if tokens.iter().all(Result::is_ok) {
             tokens.into_iter().map(Result::unwrap).collect()
@@ -392,11 +1049,11 @@ mod test {
             vec![
                 Dict,
                 String(&b"bar"[..]),
-                Num(&"1"[..]),
+                Num("1"),
                 String(&b"foo"[..]),
                 List,
-                Num(&"2"[..]),
-                Num(&"3"[..]),
+                Num("2"),
+                Num("3"),
                 End,
                 End,
             ]
@@ -422,18 +1079,41 @@ mod test {
     fn negative_numbers_and_zero_should_parse() {
         use self::Token::*;
         let tokens: Vec<_> = decode_tokens(b"i0ei-1e");
-        assert_eq!(tokens, vec![Num(&"0"), Num(&"-1")],);
+        assert_eq!(tokens, vec![Num("0"), Num("-1")],);
     }
 
     #[test]
     fn negative_zero_is_illegal() {
-        decode_err(b"i-0e", "got '0'");
+        decode_err(b"i-0e", "integer at offset 2: negative zero is not allowed");
+        decode_err(b"i-0", "integer at offset 2: negative zero is not allowed");
     }
 
     #[test]
     fn leading_zeros_are_illegal() {
-        decode_err(b"i01e", "got '1'");
-        decode_err(b"i-01e", "got '0'");
+        decode_err(
+            b"i01e",
+            "integer at offset 2: leading zeros are not allowed",
+        );
+        decode_err(
+            b"i00e",
+            "integer at offset 2: leading zeros are not allowed",
+        );
+        decode_err(
+            b"i-01e",
+            "integer at offset 2: leading zeros are not allowed",
+        );
+        decode_err(
+            b"01:a",
+            "string length at offset 1: leading zeros are not allowed",
+        );
+    }
+
+    #[test]
+    fn lone_minus_signs_are_illegal() {
+        decode_err(
+            b"i-e",
+            "integer at offset 2: a minus sign must be followed by digits",
+        );
     }
 
     #[test]
@@ -463,7 +1143,7 @@ mod test {
 
     #[test]
     fn ints_must_have_bodies() {
-        decode_err(b"ie", r"Expected.*got 'e'");
+        decode_err(b"ie", "integer at offset 1: at least one digit is required");
     }
 
     #[test]
@@ -491,6 +1171,347 @@ mod test {
             .is_err());
     }
 
+    #[test]
+    fn default_depth_should_be_documented_value() {
+        let mut msg = Vec::new();
+        msg.extend(iter::repeat(b'l').take(Decoder::DEFAULT_MAX_DEPTH + 1));
+        msg.extend(iter::repeat(b'e').take(Decoder::DEFAULT_MAX_DEPTH + 1));
+
+        let is_ok = |decoder: Decoder| decoder.tokens().last().unwrap().is_ok();
+        assert!(is_ok(Decoder::new(&msg[1..msg.len() - 1])));
+        assert!(!is_ok(Decoder::new(&msg)));
+        assert!(is_ok(Decoder::new_unlimited(&msg)));
+    }
+
+    #[test]
+    fn hardened_limits_should_be_documented_values() {
+        let is_ok = |msg: &[u8]| {
+            Decoder::new_hardened(msg)
+                .tokens()
+                .all(|token| token.is_ok())
+        };
+
+        let depth = Decoder::HARDENED_MAX_DEPTH;
+        let nested = [vec![b'l'; depth], vec![b'e'; depth]].concat();
+        assert!(is_ok(&nested));
+        assert!(!is_ok(&[&b"l"[..], &nested, b"e"].concat()));
+
+        let length = Decoder::HARDENED_MAX_STRING_LENGTH;
+        let string = |length: usize| [format!("{}:", length).into_bytes(), vec![b'x'; length]];
+        assert!(is_ok(&string(length).concat()));
+        assert!(!is_ok(&string(length + 1).concat()));
+
+        let total = Decoder::HARDENED_MAX_TOTAL_STRING_BYTES / length;
+        let strings = |count: usize| {
+            let mut msg = vec![b'l'];
+            for _ in 0..count {
+                msg.extend(string(length).concat());
+            }
+            msg.push(b'e');
+            msg
+        };
+        assert!(is_ok(&strings(total)));
+        assert!(!is_ok(&strings(total + 1)));
+    }
+
+    #[test]
+    fn string_length_should_be_limited() {
+        let mut decoder = Decoder::new(b"3:foo").with_max_string_length(3);
+        assert!(decoder.next_object().is_ok());
+
+        let mut decoder = Decoder::new(b"4:quux").with_max_string_length(3);
+        let error = decoder.next_object().err().unwrap();
+        assert_eq!(error.offset(), Some(0));
+        assert!(error.to_string().contains("exceeds the maximum"));
+    }
+
+    #[test]
+    fn total_string_length_should_be_limited() {
+        let decoder = Decoder::new(b"l3:foo3:bare").with_max_total_string_bytes(6);
+        assert!(decoder.tokens().all(|token| token.is_ok()));
+
+        let decoder = Decoder::new(b"l3:foo3:bar1:xe").with_max_total_string_bytes(6);
+        let error = decoder.tokens().find_map(Result::err).unwrap();
+        assert_eq!(error.offset(), Some(11));
+        assert!(error.to_string().contains("total length 7"));
+    }
+
+    #[test]
+    fn decoders_are_sync_send() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+        is_send::<Decoder>();
+        is_sync::<Decoder>();
+    }
+
+    #[test]
+    fn key_validator_should_only_see_dict_keys() {
+        let msg = b"d1:ad1:bl2:xxee1:ci1ee";
+        let validate = |key: &[u8]| key.len() == 1;
+        assert!(Decoder::new(msg)
+            .with_key_validator(validate)
+            .tokens()
+            .all(|token| token.is_ok()));
+
+        let msg = b"d1:ai1e2:bbi2ee";
+        let error = Decoder::new(msg)
+            .with_key_validator(validate)
+            .tokens()
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(error.offset(), Some(7));
+        assert!(error.to_string().contains(r#"key "bb" was rejected"#));
+    }
+
+    #[test]
+    fn whitespace_should_only_be_skipped_when_allowed() {
+        let msg = b"d\n  3:bar 3: a \n  3:foo l i1e\ti2e e\ne\n";
+        assert!(Decoder::new(msg).tokens().any(|token| token.is_err()));
+
+        let tokens: Vec<_> = Decoder::new(msg)
+            .with_allow_whitespace(true)
+            .tokens()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Dict,
+                Token::String(b"bar"),
+                Token::String(b" a "),
+                Token::String(b"foo"),
+                Token::List,
+                Token::Num("1"),
+                Token::Num("2"),
+                Token::End,
+                Token::End,
+            ]
+        );
+    }
+
+    #[test]
+    fn stats_should_count_tokens() {
+        let mut decoder = Decoder::new(b"d3:fooli1ei2ee3:quxd1:a2:bceei3e").with_stats();
+        while decoder.next_object().unwrap().is_some() {}
+
+        assert_eq!(
+            decoder.stats(),
+            DecodeStats {
+                integers: 3,
+                byte_strings: 4,
+                lists: 1,
+                dicts: 2,
+                string_bytes: 9,
+                max_depth: 2,
+            }
+        );
+        assert_eq!(Decoder::new(b"le").stats(), DecodeStats::default());
+    }
+
+    #[test]
+    fn list_next_as_should_decode_items() {
+        use crate::encoding::AsString;
+
+        let mut decoder = Decoder::new(b"li1e3:fooi-1ee");
+        let mut list = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_into_list()
+            .unwrap();
+
+        assert_eq!(list.next_as::<u8>().unwrap(), Some(1));
+        assert_eq!(
+            list.next_as::<AsString<Vec<u8>>>().unwrap(),
+            Some(AsString(b"foo".to_vec()))
+        );
+        assert!(list.next_as::<u8>().is_err());
+    }
+
+    #[test]
+    fn dict_next_pair_as_should_decode_values() {
+        let mut decoder = Decoder::new(b"d3:bari1e3:foo3:baze");
+        let mut dict = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_into_dictionary()
+            .unwrap();
+
+        assert_eq!(dict.next_pair_as::<u8>().unwrap(), Some((&b"bar"[..], 1)));
+        let error = dict.next_pair_as::<u8>().unwrap_err();
+        assert!(format!("{:?}", error).contains(r#"context: Some("foo")"#));
+    }
+
+    #[test]
+    fn bounded_decoder_should_report_objects_past_declared_length() {
+        let exceeds = |offset| {
+            Error::from(StructureError::ExceedsDeclaredLength { declared_length: 5 })
+                .at_offset(offset)
+        };
+        let first_error = |buffer, length| {
+            Decoder::new_bounded(buffer, length)
+                .tokens()
+                .find_map(Result::err)
+        };
+
+        assert_eq!(first_error(b"li1ei2ee", 5), Some(exceeds(5)));
+        assert_eq!(first_error(b"i1234e", 5), Some(exceeds(5)));
+        assert_eq!(first_error(b"4:abcd", 5), Some(exceeds(5)));
+        assert_eq!(first_error(b"li1eeli2ee", 5), None);
+
+        // a buffer shorter than declared is just truncated
+        let error = first_error(b"li1e", 5).unwrap();
+        assert_eq!(
+            error,
+            Error::from(StructureError::UnexpectedEof).at_offset(4)
+        );
+    }
+
+    #[test]
+    fn overlong_strings_should_report_their_length() {
+        let error = Decoder::new(b"5:abc").tokens().next().unwrap().unwrap_err();
+        assert_eq!(
+            error,
+            Error::from(StructureError::UnexpectedEof).at_offset(5)
+        );
+
+        for &length in &[usize::max_value(), isize::max_value() as usize] {
+            let message = format!("li1e{}:ae", length);
+            let error = Decoder::new(message.as_bytes())
+                .tokens()
+                .find_map(Result::err)
+                .unwrap();
+            assert_eq!(
+                error,
+                Error::from(StructureError::StringLengthExceedsInput {
+                    length,
+                    remaining: 2,
+                })
+                .at_offset(message.len())
+            );
+        }
+    }
+
+    #[test]
+    fn decode_all_lenient_should_resynchronize_after_errors() {
+        let results = decode_all_lenient(b"li1eeli01eeei2e4:ab");
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0], Ok(Value::List(vec![Value::Integer(1)])));
+        assert_eq!(results[1].as_ref().unwrap_err().offset(), Some(8));
+        assert_eq!(results[2], Ok(Value::Integer(2)));
+        assert_eq!(results[3].as_ref().unwrap_err().offset(), Some(19));
+
+        // the items of a corrupt object are taken for top-level objects
+        let results = decode_all_lenient(b"d1:bi1e1:ai2ee");
+        assert!(results[0].is_err());
+        assert_eq!(results[1], Ok(Value::Bytes(b"b"[..].into())));
+
+        assert!(decode_all_lenient(b"").is_empty());
+        assert_eq!(decode_all_lenient(b"x").len(), 1);
+    }
+
+    #[test]
+    fn decode_exact_should_reject_trailing_data() {
+        assert_eq!(decode_exact::<Vec<u32>>(b"li1ee").unwrap(), vec![1]);
+
+        let error = decode_exact::<Vec<u32>>(b"li1eei2e").unwrap_err();
+        assert_eq!(
+            error,
+            Error::from(StructureError::TrailingData).at_offset(5)
+        );
+        assert!(decode_exact::<Vec<u32>>(b"li1eex").is_err());
+        assert!(decode_exact::<u32>(b"").is_err());
+    }
+
+    #[test]
+    fn raw_info_dict_should_return_exact_bytes() {
+        // unknown keys inside the info dict must be preserved
+        let torrent = b"d4:infod4:name1:a8:x-customlee8:url-listlee";
+        assert_eq!(raw_info_dict(torrent).unwrap(), b"d4:name1:a8:x-customlee");
+
+        assert_eq!(
+            raw_info_dict(b"d8:announce3:urle").unwrap_err(),
+            Error::missing_field("info")
+        );
+        assert!(raw_info_dict(b"d4:infoi1ee").is_err());
+        // the rest of the torrent is validated as well
+        assert!(raw_info_dict(b"d4:infode").is_err());
+        assert!(raw_info_dict(b"d4:infode1:ai1ee").is_err());
+    }
+
+    #[test]
+    fn next_pair_spanned_should_cover_the_encoded_key() {
+        let source = b"d0:i1e10:0123456789i2e1:ale e";
+        let mut decoder = Decoder::new(source).with_allow_whitespace(true);
+        let mut dict = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_into_dictionary()
+            .unwrap();
+
+        let mut spans = Vec::new();
+        while let Some((key, span, _)) = dict.next_pair_spanned().unwrap() {
+            assert!(source[span.clone()].ends_with(key));
+            spans.push(span);
+        }
+        assert_eq!(spans, [1..3, 6..19, 22..25]);
+    }
+
+    #[test]
+    fn owned_objects_should_outlive_the_input() {
+        let values = {
+            let input = b"d1:ai1ee3:foo".to_vec();
+            let mut decoder = Decoder::new(&input);
+            let mut values = Vec::new();
+            while let Some(value) = decoder.next_object_owned().unwrap() {
+                values.push(value);
+            }
+            values
+        };
+
+        let mut dict = BTreeMap::new();
+        dict.insert(b"a"[..].into(), Value::Integer(1));
+        assert_eq!(values, [Value::Dict(dict), Value::Bytes(b"foo"[..].into())]);
+
+        let mut decoder = Decoder::new(b"li1ei01ee");
+        assert!(decoder.next_object_owned().is_err());
+        assert!(decoder.next_object_owned().is_err());
+    }
+
+    #[test]
+    fn source_slice_should_check_bounds() {
+        let mut decoder = Decoder::new_bounded(b"i1ei2e", 3);
+        assert!(decoder.next_object().unwrap().is_some());
+
+        assert_eq!(decoder.source_slice(0..3), Some(&b"i1e"[..]));
+        assert_eq!(decoder.source_slice(3..3), Some(&b""[..]));
+        assert_eq!(decoder.source_slice(3..6), None);
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = decoder.source_slice(2..1);
+        assert_eq!(reversed, None);
+    }
+
+    #[test]
+    fn into_inner_should_allow_resuming() {
+        let mut decoder = Decoder::new(b"li1eed1:ai2ee");
+        assert!(decoder.next_object().unwrap().is_some());
+
+        let (source, offset) = decoder.into_inner();
+        assert_eq!(offset, 5);
+
+        let mut decoder = Decoder::new(&source[offset..]);
+        let mut dict = decoder
+            .next_object()
+            .unwrap()
+            .unwrap()
+            .try_into_dictionary()
+            .unwrap();
+        assert_eq!(dict.next_pair_as::<u8>().unwrap(), Some((&b"a"[..], 2)));
+    }
+
     #[test]
     fn dict_drop_should_consume_struct() {
         let mut decoder = Decoder::new(b"d3:fooi1e3:quxi2eei1000e");
@@ -509,6 +1530,81 @@ mod test {
         assert_eq!(token, Token::Num("1000"));
     }
 
+    fn error_offset(msg: &[u8]) -> Option<usize> {
+        let decoder = Decoder::new(msg);
+        let offset = decoder.tokens().find_map(Result::err).unwrap().offset();
+
+        // latched errors must keep their offset
+        let mut decoder = Decoder::new(msg);
+        while decoder.next_token().is_ok() {}
+        assert_eq!(decoder.next_token().unwrap_err().offset(), offset);
+
+        offset
+    }
+
+    #[test]
+    fn errors_should_carry_offsets() {
+        assert_eq!(error_offset(b"d3:fooi01ee"), Some(8));
+        assert_eq!(error_offset(b"li1ex"), Some(4));
+        assert_eq!(error_offset(b"d3:fooi1e3:bari1ee"), Some(9));
+        assert_eq!(error_offset(b"5:abc"), Some(5));
+        assert_eq!(error_offset(b"li1e"), Some(4));
+    }
+
+    #[test]
+    fn display_with_source_should_point_at_offset() {
+        let source = b"d3:fooi1e3:bari1ee";
+        let error = Decoder::new(source).tokens().find_map(Result::err).unwrap();
+        let rendered = error.display_with_source(source);
+
+        assert!(rendered.starts_with(&error.to_string()));
+        assert!(rendered.ends_with("  | d3:fooi1e3:bari1ee\n  |          ^"));
+    }
+
+    #[test]
+    fn take_object_should_advance_input() {
+        let mut input: &[u8] = b"d3:fooi1eeli2eei3e";
+
+        assert!(take_object(&mut input).is_ok());
+        assert_eq!(input, b"li2eei3e");
+        assert!(take_object(&mut input).is_ok());
+        assert_eq!(input, b"i3e");
+        assert_eq!(take_object(&mut input).unwrap(), Value::Integer(3));
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn empty_input_should_be_told_apart_from_truncated_input() {
+        let mut empty: &[u8] = b"";
+        let mut truncated: &[u8] = b"d3:foo";
+
+        assert!(take_object(&mut empty).unwrap_err().is_empty_input());
+        assert!(!take_object(&mut truncated).unwrap_err().is_empty_input());
+        assert!(decode_exact::<Value>(b"").unwrap_err().is_empty_input());
+        assert!(!decode_exact::<Value>(b"d3:foo")
+            .unwrap_err()
+            .is_empty_input());
+        assert!(raw_info_dict(b"").unwrap_err().is_empty_input());
+        assert!(Value::from_bencode(b"").unwrap_err().is_empty_input());
+        assert!(Value::from_bencode_limited(b"", 1, 1)
+            .unwrap_err()
+            .is_empty_input());
+        assert!(!Value::from_bencode(b"d3:foo").unwrap_err().is_empty_input());
+
+        // the decoder itself reports the end of empty input as such
+        assert!(Decoder::new(b"").next_object().unwrap().is_none());
+    }
+
+    #[test]
+    fn take_object_should_not_advance_on_error() {
+        let mut input: &[u8] = b"li1e";
+        assert!(take_object(&mut input).is_err());
+        assert_eq!(input, b"li1e");
+
+        let mut input: &[u8] = b"";
+        assert!(take_object(&mut input).is_err());
+    }
+
     #[test]
     fn bytes_or_should_work_on_bytes() {
         assert_eq!(
@@ -580,10 +1676,7 @@ mod test {
 
     #[test]
     fn integer_str_or_should_work_on_int() {
-        assert_eq!(
-            Ok(&"123"[..]),
-            Object::Integer("123").integer_or(Err("failure"))
-        );
+        assert_eq!(Ok("123"), Object::Integer("123").integer_or(Err("failure")));
     }
 
     #[test]
@@ -615,7 +1708,7 @@ mod test {
     #[test]
     fn integer_str_or_else_should_work_on_int() {
         assert_eq!(
-            Ok(&"123"[..]),
+            Ok("123"),
             Object::Integer("123").integer_or_else(|_| Err("failure"))
         );
     }
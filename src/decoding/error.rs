@@ -3,7 +3,10 @@ use alloc::{
     str::Utf8Error,
     string::{FromUtf8Error, String, ToString},
 };
-use core::{fmt::Display, num::ParseIntError};
+use core::{
+    fmt::{self, Display},
+    num::ParseIntError,
+};
 
 #[cfg(feature = "std")]
 use std::sync::Arc;
@@ -12,47 +15,137 @@ use snafu::Snafu;
 
 use crate::state_tracker;
 
-#[derive(Debug, Clone, Snafu)]
+#[derive(Debug, Clone, PartialEq, Snafu)]
 pub struct Error {
     context: Option<String>,
+    offset: Option<usize>,
     source: ErrorKind,
 }
 
 // An enumeration of potential errors that appear during bencode deserialization.
-#[derive(Debug, Clone, Snafu)]
+#[derive(Debug, Clone)]
 pub enum ErrorKind {
     /// Error that occurs if the serialized structure contains invalid semantics.
     #[cfg(feature = "std")]
-    #[snafu(display("malformed content discovered: {}", source))]
-    MalformedContent { source: Arc<dyn std::error::Error + Send + Sync> },
+    MalformedContent {
+        source: Arc<dyn std::error::Error + Send + Sync>,
+    },
 
     /// Error that occurs if the serialized structure contains invalid semantics.
     #[cfg(not(feature = "std"))]
-    #[snafu(display("malformed content discovered"))]
     MalformedContent,
 
     /// Error that occurs if the serialized structure is incomplete.
-    #[snafu(display("missing field: {}", field))]
     MissingField { field: String },
 
+    /// Error that occurs if there is no object to decode at all, as opposed to an object that is
+    /// cut off (which is reported as a structure error).
+    EmptyInput,
+
     /// Error in the bencode structure (e.g. a missing field and seperator).
-    #[snafu(display("bencode encoding corrupted ({})", source))]
     StructureError {
         source: state_tracker::StructureError,
     },
 
+    /// Error that occurs if the input is nested deeper than the
+    /// [`FromBencode::EXPECTED_RECURSION_DEPTH`] of the type it is decoded into.
+    ///
+    /// [`FromBencode::EXPECTED_RECURSION_DEPTH`]: crate::decoding::FromBencode::EXPECTED_RECURSION_DEPTH
+    NestingTooDeepForType {
+        type_name: String,
+        source: state_tracker::StructureError,
+    },
+
     /// Error that occurs if the serialized structure contains an unexpected field.
-    #[snafu(display("unexpected field: {}", field))]
     UnexpectedField { field: String },
 
     /// Error through an unexpected bencode token during deserialization.
-    #[snafu(display("discovered {} but expected {}", expected, discovered))]
     UnexpectedToken {
         expected: String,
         discovered: String,
     },
 }
 
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::ErrorKind::*;
+
+        match self {
+            #[cfg(feature = "std")]
+            MalformedContent { source } => write!(f, "malformed content discovered: {}", source),
+            #[cfg(not(feature = "std"))]
+            MalformedContent => write!(f, "malformed content discovered"),
+            MissingField { field } => write!(f, "missing field: {}", field),
+            EmptyInput => write!(f, "no object to decode in empty input"),
+            StructureError { source } => write!(f, "bencode encoding corrupted ({})", source),
+            NestingTooDeepForType { type_name, source } => {
+                write!(f, "input nested too deeply for {} ({})", type_name, source)
+            },
+            UnexpectedField { field } => write!(f, "unexpected field: {}", field),
+            UnexpectedToken {
+                expected,
+                discovered,
+            } => write!(f, "discovered {} but expected {}", expected, discovered),
+        }
+    }
+}
+
+// Implemented by hand rather than derived: a derived `source()` would hand out the `Arc` around
+// a malformed content cause instead of the cause itself, which callers then can't downcast.
+impl snafu::Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn snafu::Error + 'static)> {
+        use self::ErrorKind::*;
+
+        match self {
+            #[cfg(feature = "std")]
+            MalformedContent { source } => Some(&**source),
+            StructureError { source } | NestingTooDeepForType { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Errors wrapped in `MalformedContent` are compared by their message.
+impl PartialEq for ErrorKind {
+    fn eq(&self, other: &Self) -> bool {
+        use self::ErrorKind::*;
+
+        match (self, other) {
+            #[cfg(feature = "std")]
+            (MalformedContent { source: a }, MalformedContent { source: b }) => {
+                Arc::ptr_eq(a, b) || a.to_string() == b.to_string()
+            },
+            #[cfg(not(feature = "std"))]
+            (MalformedContent, MalformedContent) => true,
+            (EmptyInput, EmptyInput) => true,
+            (MissingField { field: a }, MissingField { field: b }) => a == b,
+            (StructureError { source: a }, StructureError { source: b }) => a == b,
+            (
+                NestingTooDeepForType {
+                    type_name: a_type_name,
+                    source: a_source,
+                },
+                NestingTooDeepForType {
+                    type_name: b_type_name,
+                    source: b_source,
+                },
+            ) => a_type_name == b_type_name && a_source == b_source,
+            (UnexpectedField { field: a }, UnexpectedField { field: b }) => a == b,
+            (
+                UnexpectedToken {
+                    expected: a_expected,
+                    discovered: a_discovered,
+                },
+                UnexpectedToken {
+                    expected: b_expected,
+                    discovered: b_discovered,
+                },
+            ) => a_expected == b_expected && a_discovered == b_discovered,
+            _ => false,
+        }
+    }
+}
+
 pub trait ResultExt {
     fn context(self, context: impl Display) -> Self;
 }
@@ -68,6 +161,126 @@ impl Error {
         self
     }
 
+    /// The byte offset into the decoded buffer at which this error was detected, if known.
+    ///
+    /// Errors produced by the [`Decoder`] itself carry an offset; errors raised by
+    /// [`FromBencode`] implementations generally don't.
+    ///
+    /// [`Decoder`]: crate::decoding::Decoder
+    /// [`FromBencode`]: crate::decoding::FromBencode
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// The kind of token that was expected, if this is an unexpected-token error.
+    ///
+    /// ```
+    /// use bendy::decoding::FromBencode;
+    ///
+    /// let error = Vec::<u8>::from_bencode(b"3:foo").unwrap_err();
+    ///
+    /// assert_eq!(error.expected(), Some("List"));
+    /// assert_eq!(error.found(), Some("String"));
+    /// ```
+    pub fn expected(&self) -> Option<&str> {
+        match &self.source {
+            ErrorKind::UnexpectedToken { expected, .. } => Some(expected),
+            _ => None,
+        }
+    }
+
+    /// The kind of token that was actually found, if this is an unexpected-token error.
+    pub fn found(&self) -> Option<&str> {
+        match &self.source {
+            ErrorKind::UnexpectedToken { discovered, .. } => Some(discovered),
+            _ => None,
+        }
+    }
+
+    /// Whether decoding failed because the input contained no object at all, rather than a
+    /// truncated one. Protocols where an empty message is meaningful can use this to tell the two
+    /// apart.
+    ///
+    /// ```
+    /// use bendy::decoding::FromBencode;
+    ///
+    /// assert!(u32::from_bencode(b"").unwrap_err().is_empty_input());
+    /// assert!(!u32::from_bencode(b"i1").unwrap_err().is_empty_input());
+    /// ```
+    pub fn is_empty_input(&self) -> bool {
+        match self.source {
+            ErrorKind::EmptyInput => true,
+            _ => false,
+        }
+    }
+
+    /// Name `T` in an error about the input being nested too deeply, for errors of decoders whose
+    /// depth limit is the [`FromBencode::EXPECTED_RECURSION_DEPTH`] of `T`.
+    ///
+    /// [`FromBencode::EXPECTED_RECURSION_DEPTH`]: crate::decoding::FromBencode::EXPECTED_RECURSION_DEPTH
+    pub(crate) fn in_type<T: ?Sized>(mut self) -> Self {
+        if let ErrorKind::StructureError {
+            source: source @ state_tracker::StructureError::NestingTooDeep { .. },
+        } = &self.source
+        {
+            self.source = ErrorKind::NestingTooDeepForType {
+                type_name: core::any::type_name::<T>().to_string(),
+                source: source.clone(),
+            };
+        }
+        self
+    }
+
+    /// Attach an offset to this error, unless it already carries one.
+    pub(crate) fn at_offset(mut self, offset: usize) -> Self {
+        self.offset.get_or_insert(offset);
+        self
+    }
+
+    /// Render this error together with a snippet of the `source` it was decoded from, with a
+    /// caret pointing at the offending byte. Non-printable bytes are shown as `.`.
+    ///
+    /// If the error doesn't carry an [offset](Error::offset), this is equivalent to the
+    /// `Display` output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bendy::decoding::Decoder;
+    ///
+    /// let source = b"d3:fooi01ee";
+    /// let error = Decoder::new(source).tokens().find_map(Result::err).unwrap();
+    ///
+    /// assert!(error.display_with_source(source).ends_with("| d3:fooi01ee\n  |         ^"));
+    /// ```
+    pub fn display_with_source(&self, source: &[u8]) -> String {
+        const CONTEXT: usize = 16;
+
+        let offset = match self.offset {
+            Some(offset) => offset,
+            None => return self.to_string(),
+        };
+
+        let start = offset.saturating_sub(CONTEXT).min(source.len());
+        let end = offset.saturating_add(CONTEXT + 1).min(source.len());
+        let snippet: String = source[start..end]
+            .iter()
+            .map(|&byte| match byte {
+                b' '..=b'~' => byte as char,
+                _ => '.',
+            })
+            .collect();
+
+        format!(
+            "{}\n --> offset {}\n  |\n  | {}\n  | {:>width$}",
+            self,
+            offset,
+            snippet,
+            "^",
+            width = offset - start + 1
+        )
+    }
+
     /// Raised when there is a general error while deserializing a type.
     /// The message should not be capitalized and should not end with a period.
     #[cfg(feature = "std")]
@@ -111,6 +324,7 @@ impl From<ErrorKind> for Error {
     fn from(kind: ErrorKind) -> Self {
         Self {
             context: None,
+            offset: None,
             source: kind,
         }
     }
@@ -140,6 +354,17 @@ impl From<ParseIntError> for Error {
     }
 }
 
+/// Wraps the error into an [`InvalidData`] IO error, so that decoding from an IO source can
+/// report errors as [`std::io::Error`]s.
+///
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
 impl<T> ResultExt for Result<T, Error> {
     fn context(self, context: impl Display) -> Self {
         self.map_err(|err| err.context(context))
@@ -148,7 +373,7 @@ impl<T> ResultExt for Result<T, Error> {
 
 #[test]
 fn decoding_errors_are_sync_send() {
-    use crate::decoding::error::{ ErrorKind, Error };
+    use crate::decoding::error::{Error, ErrorKind};
     fn is_send<T: Send>() {}
     fn is_sync<T: Sync>() {}
     is_send::<Error>();
@@ -156,3 +381,66 @@ fn decoding_errors_are_sync_send() {
     is_sync::<Error>();
     is_sync::<ErrorKind>();
 }
+
+#[test]
+fn decoding_errors_should_be_comparable() {
+    use crate::decoding::FromBencode;
+
+    assert_eq!(
+        u8::from_bencode(b"i300e").unwrap_err(),
+        u8::from_bencode(b"i300e").unwrap_err()
+    );
+    assert_ne!(
+        u8::from_bencode(b"i300e").unwrap_err(),
+        Error::missing_field("foo")
+    );
+    assert_eq!(
+        Error::missing_field("foo").context("bar"),
+        Error::missing_field("foo").context("bar")
+    );
+    assert_ne!(Error::missing_field("foo"), Error::unexpected_field("foo"));
+}
+
+#[test]
+fn unexpected_token_errors_should_expose_token_kinds() {
+    use crate::decoding::FromBencode;
+
+    let error = u8::from_bencode(b"3:foo").unwrap_err();
+    assert_eq!(error.expected(), Some("Num"));
+    assert_eq!(error.found(), Some("String"));
+
+    let error = Error::missing_field("foo");
+    assert_eq!(error.expected(), None);
+    assert_eq!(error.found(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn decoding_errors_should_chain_their_cause() {
+    use crate::decoding::FromBencode;
+    use std::error::Error as _;
+
+    let error = u8::from_bencode(b"i300e").unwrap_err();
+    let kind = error.source().expect("error kind");
+    let cause = kind.source().expect("wrapped cause");
+    assert!(cause.downcast_ref::<ParseIntError>().is_some());
+
+    let error = String::from_bencode(b"1:\xff").unwrap_err();
+    let cause = error.source().and_then(|kind| kind.source()).unwrap();
+    assert!(cause.downcast_ref::<std::string::FromUtf8Error>().is_some());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn decoding_errors_should_convert_into_io_errors() {
+    use crate::decoding::FromBencode;
+
+    fn decode(bytes: &[u8]) -> std::io::Result<u8> {
+        Ok(u8::from_bencode(bytes)?)
+    }
+
+    let error = decode(b"i300e").unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(error.get_ref().unwrap().downcast_ref::<Error>().is_some());
+    assert_eq!(decode(b"i3e").unwrap(), 3);
+}
@@ -0,0 +1,153 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{
+    decoding::{Error, Tokens},
+    state_tracker::Token,
+};
+
+/// A structural event in a bencode stream
+///
+/// Unlike a raw [`Token`], an event tells apart the ends of lists and dictionaries as well as
+/// dictionary keys and byte string values.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Event<'a> {
+    /// The beginning of a list
+    ListBegin,
+    /// The end of a list
+    ListEnd,
+    /// The beginning of a dictionary
+    DictBegin,
+    /// The end of a dictionary
+    DictEnd,
+    /// A dictionary key. The next event belongs to its value.
+    Key(&'a [u8]),
+    /// An unparsed integer
+    Integer(&'a str),
+    /// A byte string that is not a dictionary key
+    Bytes(&'a [u8]),
+}
+
+/// The kind of structure an [`Events`] iterator is currently inside of
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Context {
+    List,
+    DictKey,
+    DictValue,
+}
+
+/// Iterator over the structural events of the input stream. See [`Tokens::events`].
+pub struct Events<'a> {
+    tokens: Tokens<'a>,
+    stack: Vec<Context>,
+}
+
+impl<'a> Events<'a> {
+    pub(crate) fn new(tokens: Tokens<'a>) -> Self {
+        Events {
+            tokens,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Result<Event<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = match self.tokens.next()? {
+            Ok(token) => token,
+            Err(err) => return Some(Err(err)),
+        };
+
+        // The token stream has already been validated, so the context stack can't underflow
+        // and dictionary keys are guaranteed to be strings.
+        if token == Token::End {
+            return Some(Ok(match self.stack.pop() {
+                Some(Context::List) => Event::ListEnd,
+                _ => Event::DictEnd,
+            }));
+        }
+
+        let is_key = match self.stack.last_mut() {
+            Some(context @ Context::DictKey) => {
+                *context = Context::DictValue;
+                true
+            },
+            Some(context @ Context::DictValue) => {
+                *context = Context::DictKey;
+                false
+            },
+            _ => false,
+        };
+
+        let event = match token {
+            Token::List => {
+                self.stack.push(Context::List);
+                Event::ListBegin
+            },
+            Token::Dict => {
+                self.stack.push(Context::DictKey);
+                Event::DictBegin
+            },
+            Token::String(bytes) if is_key => Event::Key(bytes),
+            Token::String(bytes) => Event::Bytes(bytes),
+            Token::Num(number) => Event::Integer(number),
+            Token::End => unreachable!("End tokens are handled above"),
+        };
+
+        Some(Ok(event))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(not(feature = "std"))]
+    use alloc::{vec, vec::Vec};
+
+    use super::*;
+    use crate::decoding::Decoder;
+
+    #[test]
+    fn events_should_distinguish_keys_and_ends() {
+        use self::Event::*;
+
+        let events = Decoder::new(b"d3:bar3:baz3:fooli1e3:quxd1:alee0:ee")
+            .tokens()
+            .events()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                DictBegin,
+                Key(b"bar"),
+                Bytes(b"baz"),
+                Key(b"foo"),
+                ListBegin,
+                Integer("1"),
+                Bytes(b"qux"),
+                DictBegin,
+                Key(b"a"),
+                ListBegin,
+                ListEnd,
+                DictEnd,
+                Bytes(b""),
+                ListEnd,
+                DictEnd,
+            ]
+        );
+    }
+
+    #[test]
+    fn events_should_report_errors() {
+        let mut events = Decoder::new(b"d3:fooi1e").tokens().events();
+
+        assert_eq!(events.next().unwrap().unwrap(), Event::DictBegin);
+        assert_eq!(events.next().unwrap().unwrap(), Event::Key(b"foo"));
+        assert_eq!(events.next().unwrap().unwrap(), Event::Integer("1"));
+        assert!(events.next().unwrap().is_err());
+        assert!(events.next().is_none());
+    }
+}
@@ -1,34 +1,69 @@
 #[cfg(not(feature = "std"))]
-use alloc::{collections::BTreeMap, rc::Rc, string::String, vec::Vec};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BinaryHeap, LinkedList},
+    rc::Rc,
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
 
 #[cfg(feature = "std")]
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BinaryHeap, HashMap, LinkedList},
+    ffi::CString,
     hash::{BuildHasher, Hash},
     rc::Rc,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use core::convert::TryFrom;
+
+#[cfg(feature = "std")]
+use snafu::Snafu;
+
+#[cfg(feature = "std")]
+use crate::encoding::{Millis, Seconds};
 use crate::{
-    decoding::{Decoder, Error, Object},
-    encoding::AsString,
-    state_tracker::StructureError,
+    decoding::{Decoder, Error, ErrorKind, ListDecoder, Object},
+    encoding::{AsString, Saturating},
 };
 
 ///Basic trait for bencode based value deserialization.
 pub trait FromBencode {
     /// Maximum allowed depth of nested structures before the decoding should be aborted.
+    ///
+    /// [`FromBencode::from_bencode`] and [`decode_exact`](crate::decoding::decode_exact) enforce
+    /// this limit, and fail with [`ErrorKind::NestingTooDeepForType`] naming the type for input
+    /// that is nested deeper.
     const EXPECTED_RECURSION_DEPTH: usize = 2048;
 
     /// Deserialize an object from its byte representation.
+    ///
+    /// Only the first object of `bytes` is decoded, and anything after it is ignored. If `bytes`
+    /// is empty, this fails with an error for which [`Error::is_empty_input`] is true, while an
+    /// incomplete object fails with a structure error.
     fn from_bencode(bytes: &[u8]) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        let mut decoder = Decoder::new(bytes).with_max_depth(Self::EXPECTED_RECURSION_DEPTH);
+        Self::from_bencode_with_max_depth(bytes, Self::EXPECTED_RECURSION_DEPTH)
+            .map_err(Error::in_type::<Self>)
+    }
+
+    /// Deserialize an object from its byte representation, allowing it to nest up to
+    /// `max_depth` levels instead of [`FromBencode::EXPECTED_RECURSION_DEPTH`]. This is meant
+    /// for types without a static depth bound, such as recursive types.
+    fn from_bencode_with_max_depth(bytes: &[u8], max_depth: usize) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut decoder = Decoder::new(bytes).with_max_depth(max_depth);
         let object = decoder.next_object()?;
 
         object.map_or(
-            Err(Error::from(StructureError::UnexpectedEof)),
+            Err(Error::from(ErrorKind::EmptyInput)),
             Self::decode_bencode_object,
         )
     }
@@ -37,6 +72,139 @@ pub trait FromBencode {
     fn decode_bencode_object(object: Object) -> Result<Self, Error>
     where
         Self: Sized;
+
+    /// Deserialize an object from its intermediate bencode representation into an existing
+    /// value, e.g. to reuse its allocations in a loop.
+    ///
+    /// By default, this replaces `self` with a freshly decoded value and leaves it untouched on
+    /// error. Implementations that reuse `self` may leave it partially updated on error.
+    fn decode_into(&mut self, object: Object) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        *self = Self::decode_bencode_object(object)?;
+        Ok(())
+    }
+}
+
+/// A tuple that can be decoded from a list with exactly one item per element, see
+/// [`Object::try_into_tuple`].
+///
+/// This is implemented for tuples of up to 8 elements that each implement [`FromBencode`].
+pub trait FromBencodeTuple: Sized {
+    /// The number of elements of the tuple
+    const ARITY: usize;
+
+    /// Decode the tuple from the next [`FromBencodeTuple::ARITY`] items of `list`, failing if it
+    /// has fewer items. Any further items are left in `list`.
+    fn decode_prefix(list: &mut ListDecoder) -> Result<Self, Error>;
+
+    /// Decode the tuple from the items of `list`, failing if it doesn't have exactly
+    /// [`FromBencodeTuple::ARITY`] items.
+    fn decode_tuple(mut list: ListDecoder) -> Result<Self, Error> {
+        let tuple = Self::decode_prefix(&mut list)?;
+
+        let mut length = Self::ARITY;
+        while list.next_object()?.is_some() {
+            length += 1;
+        }
+        if length != Self::ARITY {
+            return Err(tuple_length_mismatch(Self::ARITY, length));
+        }
+
+        Ok(tuple)
+    }
+}
+
+/// Decode the item of a tuple at `index`, which must be present
+fn next_tuple_item<T: FromBencode>(
+    list: &mut ListDecoder,
+    index: usize,
+    arity: usize,
+) -> Result<T, Error> {
+    match list.next_as()? {
+        Some(item) => Ok(item),
+        None => Err(tuple_length_mismatch(arity, index)),
+    }
+}
+
+fn tuple_length_mismatch(arity: usize, length: usize) -> Error {
+    Error::unexpected_token(
+        format_args!("a list of {} items", arity),
+        format_args!("a list of {} items", length),
+    )
+}
+
+macro_rules! impl_from_bencode_tuple {
+    ($($arity:literal => ($($index:literal $type:ident)+))*) => {$(
+        impl<$($type: FromBencode),+> FromBencodeTuple for ($($type,)+) {
+            const ARITY: usize = $arity;
+
+            fn decode_prefix(list: &mut ListDecoder) -> Result<Self, Error> {
+                Ok(($(next_tuple_item::<$type>(list, $index, $arity)?,)+))
+            }
+        }
+    )*}
+}
+
+impl_from_bencode_tuple! {
+    1 => (0 A)
+    2 => (0 A 1 B)
+    3 => (0 A 1 B 2 C)
+    4 => (0 A 1 B 2 C 3 D)
+    5 => (0 A 1 B 2 C 3 D 4 E)
+    6 => (0 A 1 B 2 C 3 D 4 E 5 F)
+    7 => (0 A 1 B 2 C 3 D 4 E 5 F 6 G)
+    8 => (0 A 1 B 2 C 3 D 4 E 5 F 6 G 7 H)
+}
+
+/// Parse an integer of at most 64 bits, returning `None` if it is malformed or out of range.
+///
+/// This is faster than `str::parse`, mostly because it doesn't handle a leading `+`. Callers fall
+/// back to `str::parse` if this fails, both for such input and to get a descriptive error.
+///
+/// Up to 19 digits can't overflow a `u64`, so those are accumulated without any per-digit
+/// branches: invalid digits are only collected into a flag that is checked once at the end.
+fn parse_i64_or_u64(content: &str) -> Option<(bool, u64)> {
+    let (negative, digits) = match content.as_bytes().split_first() {
+        Some((b'-', digits)) => (true, digits),
+        _ => (false, content.as_bytes()),
+    };
+    // u64::max_value() has 20 digits
+    if digits.is_empty() || digits.len() > 20 {
+        return None;
+    }
+    if digits.len() == 20 {
+        return parse_u64_checked(digits).map(|magnitude| (negative, magnitude));
+    }
+
+    let mut magnitude: u64 = 0;
+    let mut invalid = false;
+    for &byte in digits {
+        let digit = byte.wrapping_sub(b'0');
+        invalid |= digit > 9;
+        magnitude = magnitude.wrapping_mul(10).wrapping_add(u64::from(digit));
+    }
+
+    if invalid {
+        None
+    } else {
+        Some((negative, magnitude))
+    }
+}
+
+/// Parse digits that might overflow a `u64`, checking each step.
+fn parse_u64_checked(digits: &[u8]) -> Option<u64> {
+    let mut magnitude: u64 = 0;
+    for &byte in digits {
+        let digit = byte.wrapping_sub(b'0');
+        if digit > 9 {
+            return None;
+        }
+        magnitude = magnitude.checked_mul(10)?.checked_add(u64::from(digit))?;
+    }
+
+    Some(magnitude)
 }
 
 macro_rules! impl_from_bencode_for_integer {
@@ -57,7 +225,95 @@ macro_rules! impl_from_bencode_for_integer {
     )*}
 }
 
-impl_from_bencode_for_integer!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+macro_rules! impl_from_bencode_for_unsigned_integer {
+    ($($type:ty)*) => {$(
+        impl FromBencode for $type {
+            const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+            fn decode_bencode_object(object: Object) -> Result<Self, Error>
+            where
+                Self: Sized,
+            {
+                let content = object.try_into_integer()?;
+                let fast = parse_i64_or_u64(content).and_then(|(negative, magnitude)| {
+                    if negative {
+                        None
+                    } else {
+                        <$type>::try_from(magnitude).ok()
+                    }
+                });
+
+                match fast {
+                    Some(number) => Ok(number),
+                    None => Ok(content.parse::<$type>()?),
+                }
+            }
+        }
+    )*}
+}
+
+macro_rules! impl_from_bencode_for_signed_integer {
+    ($($type:ty)*) => {$(
+        impl FromBencode for $type {
+            const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+            fn decode_bencode_object(object: Object) -> Result<Self, Error>
+            where
+                Self: Sized,
+            {
+                let content = object.try_into_integer()?;
+                let fast = parse_i64_or_u64(content).and_then(|(negative, magnitude)| {
+                    let number = if !negative {
+                        i64::try_from(magnitude).ok()?
+                    } else if magnitude <= i64::min_value() as u64 {
+                        // This also covers i64::min_value(), whose magnitude doesn't fit into an i64
+                        (magnitude as i64).wrapping_neg()
+                    } else {
+                        return None;
+                    };
+                    <$type>::try_from(number).ok()
+                });
+
+                match fast {
+                    Some(number) => Ok(number),
+                    None => Ok(content.parse::<$type>()?),
+                }
+            }
+        }
+    )*}
+}
+
+impl_from_bencode_for_unsigned_integer!(u8 u16 u32 u64 usize);
+impl_from_bencode_for_signed_integer!(i8 i16 i32 i64 isize);
+impl_from_bencode_for_integer!(u128 i128);
+
+macro_rules! impl_from_bencode_for_saturating_integer {
+    ($($type:ty)*) => {$(
+        impl FromBencode for Saturating<$type> {
+            const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+            fn decode_bencode_object(object: Object) -> Result<Self, Error>
+            where
+                Self: Sized,
+            {
+                let content = object.try_into_integer()?;
+                // The decoder has already checked the syntax, so parsing can only fail because
+                // the integer is out of range, in the direction given by its sign
+                let number = content.parse::<$type>().unwrap_or_else(|_| {
+                    if content.starts_with('-') {
+                        <$type>::min_value()
+                    } else {
+                        <$type>::max_value()
+                    }
+                });
+
+                Ok(Saturating(number))
+            }
+        }
+    )*}
+}
+
+impl_from_bencode_for_saturating_integer!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
 
 impl<ContentT: FromBencode> FromBencode for Vec<ContentT> {
     const EXPECTED_RECURSION_DEPTH: usize = ContentT::EXPECTED_RECURSION_DEPTH + 1;
@@ -76,6 +332,47 @@ impl<ContentT: FromBencode> FromBencode for Vec<ContentT> {
 
         Ok(results)
     }
+
+    /// Clears the vector and refills it, keeping its allocation.
+    fn decode_into(&mut self, object: Object) -> Result<(), Error> {
+        let mut list = object.try_into_list()?;
+        self.clear();
+
+        while let Some(object) = list.next_object()? {
+            self.push(ContentT::decode_bencode_object(object)?);
+        }
+
+        Ok(())
+    }
+}
+
+impl<ContentT: FromBencode> FromBencode for LinkedList<ContentT> {
+    const EXPECTED_RECURSION_DEPTH: usize = ContentT::EXPECTED_RECURSION_DEPTH + 1;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut list = object.try_into_list()?;
+        let mut results = LinkedList::new();
+
+        while let Some(object) = list.next_object()? {
+            results.push_back(ContentT::decode_bencode_object(object)?);
+        }
+
+        Ok(results)
+    }
+}
+
+impl<ContentT: FromBencode + Ord> FromBencode for BinaryHeap<ContentT> {
+    const EXPECTED_RECURSION_DEPTH: usize = ContentT::EXPECTED_RECURSION_DEPTH + 1;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Vec::decode_bencode_object(object).map(BinaryHeap::from)
+    }
 }
 
 impl FromBencode for String {
@@ -92,6 +389,83 @@ impl FromBencode for String {
     }
 }
 
+#[cfg(feature = "bytes")]
+impl FromBencode for bytes_::Bytes {
+    const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let content = object.try_into_bytes()?;
+        Ok(bytes_::Bytes::copy_from_slice(content))
+    }
+}
+
+/// Decodes a byte string of exactly 16 bytes, as written by the `ToBencode` impl of `Uuid`.
+#[cfg(feature = "uuid")]
+impl FromBencode for uuid_::Uuid {
+    const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let content = object.try_into_bytes()?;
+        uuid_::Uuid::from_slice(content).map_err(|_| {
+            Error::unexpected_token(
+                "a byte string of 16 bytes",
+                format_args!("a byte string of {} bytes", content.len()),
+            )
+        })
+    }
+}
+
+/// Decodes a byte string without a nul terminator, as written by the `ToBencode` impl of
+/// `CString`. Byte strings containing a nul byte are rejected.
+#[cfg(feature = "std")]
+impl FromBencode for CString {
+    const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let content = object.try_into_bytes()?;
+        CString::new(content).map_err(Error::malformed_content)
+    }
+}
+
+/// Decodes a byte string of exactly `N` bytes, e.g. a hash or another fixed-size binary
+/// identifier. Byte strings of any other length are rejected.
+///
+/// Together with the map impls, this allows decoding dictionaries keyed by such identifiers into
+/// a `BTreeMap<[u8; N], V>` or a `HashMap<[u8; N], V>`.
+///
+/// Only available when compiling with Rust 1.51 or newer, which stabilized const generics.
+#[rustversion::since(1.51)]
+impl<const N: usize> FromBencode for [u8; N] {
+    const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let content = object.try_into_bytes()?;
+        if content.len() != N {
+            return Err(Error::unexpected_token(
+                format_args!("a byte string of {} bytes", N),
+                format_args!("a byte string of {} bytes", content.len()),
+            ));
+        }
+
+        let mut array = [0; N];
+        array.copy_from_slice(content);
+
+        Ok(array)
+    }
+}
+
 impl<K, V> FromBencode for BTreeMap<K, V>
 where
     K: FromBencode + Ord,
@@ -155,6 +529,39 @@ impl<T: FromBencode> FromBencode for Rc<T> {
     }
 }
 
+impl<T: FromBencode> FromBencode for Box<T> {
+    const EXPECTED_RECURSION_DEPTH: usize = T::EXPECTED_RECURSION_DEPTH;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        T::decode_bencode_object(object).map(Box::new)
+    }
+}
+
+impl<ContentT: FromBencode> FromBencode for Box<[ContentT]> {
+    const EXPECTED_RECURSION_DEPTH: usize = ContentT::EXPECTED_RECURSION_DEPTH + 1;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Vec::decode_bencode_object(object).map(Vec::into_boxed_slice)
+    }
+}
+
+impl<ContentT: FromBencode> FromBencode for Arc<[ContentT]> {
+    const EXPECTED_RECURSION_DEPTH: usize = ContentT::EXPECTED_RECURSION_DEPTH + 1;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Vec::decode_bencode_object(object).map(Arc::from)
+    }
+}
+
 impl FromBencode for AsString<Vec<u8>> {
     const EXPECTED_RECURSION_DEPTH: usize = 0;
 
@@ -166,13 +573,131 @@ impl FromBencode for AsString<Vec<u8>> {
     }
 }
 
+/// A decoded integer doesn't fit into the time type it is decoded into
+#[cfg(feature = "std")]
+#[derive(Debug, Snafu)]
+#[snafu(display("time value {} is out of range: {}", value, reason))]
+struct TimeOutOfRange {
+    value: String,
+    reason: &'static str,
+}
+
+/// Decode an integer number of seconds or milliseconds into its sign and magnitude
+#[cfg(feature = "std")]
+fn decode_time_offset(object: Object, millis: bool) -> Result<(bool, Duration), Error> {
+    let content = object.try_into_integer()?;
+    let (negative, magnitude) = if content.starts_with('-') {
+        (true, &content[1..])
+    } else {
+        (false, content)
+    };
+    let magnitude = magnitude.parse::<u128>()?;
+
+    let duration = if millis {
+        u64::try_from(magnitude / 1000).ok().map(|secs| {
+            let nanos = (magnitude % 1000) as u32 * 1_000_000;
+            Duration::new(secs, nanos)
+        })
+    } else {
+        u64::try_from(magnitude).ok().map(Duration::from_secs)
+    };
+
+    match duration {
+        Some(duration) => Ok((negative, duration)),
+        None => Err(Error::malformed_content(TimeOutOfRange {
+            value: content.to_owned(),
+            reason: "too large for a duration",
+        })),
+    }
+}
+
+#[cfg(feature = "std")]
+fn decode_duration(object: Object, millis: bool) -> Result<Duration, Error> {
+    match decode_time_offset(object, millis)? {
+        (false, duration) => Ok(duration),
+        (true, duration) => Err(Error::malformed_content(TimeOutOfRange {
+            value: format!("-{:?}", duration),
+            reason: "durations can't be negative",
+        })),
+    }
+}
+
+#[cfg(feature = "std")]
+fn decode_system_time(object: Object, millis: bool) -> Result<SystemTime, Error> {
+    let (negative, offset) = decode_time_offset(object, millis)?;
+    let time = if negative {
+        UNIX_EPOCH.checked_sub(offset)
+    } else {
+        UNIX_EPOCH.checked_add(offset)
+    };
+
+    time.ok_or_else(|| {
+        Error::malformed_content(TimeOutOfRange {
+            value: format!("{}{:?}", if negative { "-" } else { "" }, offset),
+            reason: "not representable as a system time",
+        })
+    })
+}
+
+#[cfg(feature = "std")]
+impl FromBencode for Seconds<Duration> {
+    const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        decode_duration(object, false).map(Seconds)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromBencode for Millis<Duration> {
+    const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        decode_duration(object, true).map(Millis)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromBencode for Seconds<SystemTime> {
+    const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        decode_system_time(object, false).map(Seconds)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromBencode for Millis<SystemTime> {
+    const EXPECTED_RECURSION_DEPTH: usize = 0;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        decode_system_time(object, true).map(Millis)
+    }
+}
+
 #[cfg(test)]
 mod test {
 
     #[cfg(not(feature = "std"))]
-    use alloc::{format, vec::Vec};
+    use alloc::{format, string::ToString, vec, vec::Vec};
 
-    use crate::encoding::AsString;
+    use crate::{
+        decoding::decode_exact,
+        encoding::{AsString, Saturating},
+        state_tracker::StructureError,
+    };
 
     use super::*;
 
@@ -196,6 +721,259 @@ mod test {
         assert_eq!(expected_message.as_bytes(), &decoded_vector.0[..]);
     }
 
+    #[test]
+    fn slices_should_round_trip() {
+        use crate::encoding::ToBencode;
+
+        let boxed = Box::<[u32]>::from_bencode(b"li1ei2ee").unwrap();
+        assert_eq!(&*boxed, [1, 2]);
+        assert_eq!(boxed.to_bencode().unwrap(), b"li1ei2ee");
+
+        let shared = Arc::<[u32]>::from_bencode(b"li1ei2ee").unwrap();
+        assert_eq!(&*shared, [1, 2]);
+        assert_eq!(shared.to_bencode().unwrap(), b"li1ei2ee");
+    }
+
+    #[test]
+    fn binary_heaps_should_round_trip() {
+        use crate::encoding::ToBencode;
+
+        let mut heap = BinaryHeap::<u32>::from_bencode(b"li2ei3ei1ee").unwrap();
+        assert_eq!(heap.to_bencode().unwrap(), b"li1ei2ei3ee");
+        assert_eq!(heap.pop(), Some(3));
+    }
+
+    #[test]
+    fn linked_lists_should_round_trip_in_order() {
+        use crate::encoding::ToBencode;
+
+        let list = LinkedList::<u32>::from_bencode(b"li3ei1ei2ee").unwrap();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [3, 1, 2]);
+        assert_eq!(list.to_bencode().unwrap(), b"li3ei1ei2ee");
+        assert!(LinkedList::<u32>::from_bencode(b"le").unwrap().is_empty());
+    }
+
+    #[test]
+    fn int_tagged_enums_should_require_tag_and_payload() {
+        #[derive(Debug, PartialEq)]
+        struct Tagged(i64, Vec<u8>);
+
+        impl FromBencode for Tagged {
+            fn decode_bencode_object(object: Object) -> Result<Self, Error> {
+                object.decode_int_tagged_enum(|tag, payload| {
+                    Ok(Tagged(tag, payload.try_into_bytes()?.to_vec()))
+                })
+            }
+        }
+
+        assert_eq!(
+            Tagged::from_bencode(b"li-3e3:fooe").unwrap(),
+            Tagged(-3, b"foo".to_vec())
+        );
+        for invalid in &[&b"le"[..], b"li1ee", b"li1e1:a1:be", b"l1:a1:be", b"i1e"] {
+            assert!(Tagged::from_bencode(invalid).is_err(), "{:?}", invalid);
+        }
+        let error = Tagged::from_bencode(b"li1e1:ai2ee").unwrap_err();
+        assert_eq!(error.expected(), Some("End"));
+        assert_eq!(error.found(), Some("Num"));
+    }
+
+    #[test]
+    fn tuples_should_require_one_item_per_element() {
+        fn decode<T: FromBencodeTuple>(bytes: &[u8]) -> Result<T, Error> {
+            Decoder::new(bytes)
+                .next_object()?
+                .expect("Test input should not be empty")
+                .try_into_tuple()
+        }
+
+        assert_eq!(decode::<(u8,)>(b"li1ee").unwrap(), (1,));
+        assert_eq!(
+            decode::<(i64, String, Vec<u8>)>(b"li-1e2:abli2ei3eee").unwrap(),
+            (-1, String::from("ab"), vec![2, 3])
+        );
+        assert!(decode::<(u8, String)>(b"li1ei2ee").is_err());
+        assert!(decode::<(u8, u8)>(b"i1e").is_err());
+
+        let error = decode::<(u8, u8)>(b"li1ee").unwrap_err();
+        assert_eq!(error.expected(), Some("a list of 2 items"));
+        assert_eq!(error.found(), Some("a list of 1 items"));
+
+        let error = decode::<(u8, u8)>(b"li1ei2ei3ei4ee").unwrap_err();
+        assert_eq!(error.found(), Some("a list of 4 items"));
+    }
+
+    #[test]
+    fn prefixed_lists_should_keep_the_rest_as_values() {
+        use crate::value::Value;
+
+        fn decode<T: FromBencodeTuple>(bytes: &[u8]) -> Result<(T, Vec<Value<'_>>), Error> {
+            Decoder::new(bytes)
+                .next_object()?
+                .expect("Test input should not be empty")
+                .try_into_prefixed_list()
+        }
+
+        let (prefix, rest) = decode::<(u8, u8)>(b"li1ei2ee").unwrap();
+        assert_eq!(prefix, (1, 2));
+        assert!(rest.is_empty());
+
+        let (prefix, rest) = decode::<(u8,)>(b"li1e1:ad1:bi2eee").unwrap();
+        assert_eq!(prefix, (1,));
+        assert_eq!(rest[0], Value::Bytes(b"a"[..].into()));
+        assert_eq!(rest[1], Value::from_bencode(b"d1:bi2ee").unwrap());
+
+        let error = decode::<(u8, u8)>(b"li1ee").unwrap_err();
+        assert_eq!(error.found(), Some("a list of 1 items"));
+        assert!(decode::<(u8,)>(b"li1ei01ee").is_err());
+    }
+
+    #[test]
+    fn expected_depth_should_be_enforced_naming_the_type() {
+        assert!(Vec::<Vec<u8>>::from_bencode(b"lli1eee").is_ok());
+
+        let error = Vec::<u8>::from_bencode(b"lli1eee").unwrap_err();
+        let expected = Error::from(ErrorKind::NestingTooDeepForType {
+            type_name: core::any::type_name::<Vec<u8>>().into(),
+            source: StructureError::NestingTooDeep {
+                depth: 2,
+                max_depth: 1,
+            },
+        });
+        assert_eq!(error, expected.at_offset(1));
+        assert!(error.to_string().contains("Vec<u8>"), "{}", error);
+
+        let error = decode_exact::<Vec<u8>>(b"lli1eee").unwrap_err();
+        assert!(error.to_string().contains("Vec<u8>"), "{}", error);
+    }
+
+    #[test]
+    fn depth_should_be_overridable_at_runtime() {
+        use crate::encoding::ToBencode;
+
+        let boxed = Box::<Vec<Vec<u32>>>::from_bencode(b"lli1eee").unwrap();
+        assert_eq!(*boxed, [[1]]);
+        assert!(Box::<u32>::from_bencode_with_max_depth(b"li1ee", 0).is_err());
+        assert!(Vec::<Vec<u32>>::from_bencode_with_max_depth(b"lli1eee", 1).is_err());
+
+        assert!(boxed.to_bencode_with_max_depth(1).is_err());
+        assert_eq!(boxed.to_bencode_with_max_depth(2).unwrap(), b"lli1eee");
+    }
+
+    #[test]
+    fn decode_into_should_reuse_vec() {
+        let mut numbers = Vec::with_capacity(16);
+        numbers.push(42);
+
+        let mut decoder = Decoder::new(b"li1ei2ei3ee");
+        numbers
+            .decode_into(decoder.next_object().unwrap().unwrap())
+            .unwrap();
+        assert_eq!(numbers, [1, 2, 3]);
+        assert!(numbers.capacity() >= 16);
+
+        let mut decoder = Decoder::new(b"i1e");
+        assert!(numbers
+            .decode_into(decoder.next_object().unwrap().unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn decode_into_should_replace_by_default() {
+        let mut number = 7u8;
+        let mut decoder = Decoder::new(b"i1e");
+        number
+            .decode_into(decoder.next_object().unwrap().unwrap())
+            .unwrap();
+        assert_eq!(number, 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn time_wrappers_should_round_trip() {
+        use crate::encoding::ToBencode;
+
+        let duration = Duration::from_millis(90_500);
+        let encoded = Millis(duration).to_bencode().unwrap();
+        assert_eq!(encoded, b"i90500e");
+        assert_eq!(Millis::from_bencode(&encoded).unwrap(), Millis(duration));
+
+        let encoded = Seconds(duration).to_bencode().unwrap();
+        assert_eq!(encoded, b"i90e");
+        assert_eq!(
+            Seconds::from_bencode(&encoded).unwrap(),
+            Seconds(Duration::from_secs(90))
+        );
+
+        let before_epoch = UNIX_EPOCH - Duration::from_millis(1500);
+        let encoded = Millis(before_epoch).to_bencode().unwrap();
+        assert_eq!(encoded, b"i-1500e");
+        assert_eq!(
+            Millis::from_bencode(&encoded).unwrap(),
+            Millis(before_epoch)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn negative_durations_should_be_rejected() {
+        let error = Seconds::<Duration>::from_bencode(b"i-1e").unwrap_err();
+        assert!(error.to_string().contains("durations can't be negative"));
+
+        assert!(Seconds::<SystemTime>::from_bencode(b"i-1e").is_ok());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn out_of_range_times_should_be_rejected() {
+        let too_large = format!("i{}e", u128::from(u64::max_value()) + 1);
+        assert!(Seconds::<Duration>::from_bencode(too_large.as_bytes()).is_err());
+        assert!(Seconds::<SystemTime>::from_bencode(b"i18446744073709551615e").is_err());
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn bytes_should_round_trip() {
+        use crate::encoding::ToBencode;
+
+        let bytes = bytes_::Bytes::from_static(b"foo");
+        let encoded = bytes.to_bencode().unwrap();
+        assert_eq!(encoded, b"3:foo");
+        assert_eq!(bytes_::Bytes::from_bencode(&encoded).unwrap(), bytes);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuids_should_round_trip_as_16_byte_strings() {
+        use crate::encoding::ToBencode;
+
+        let uuid = uuid_::Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+        let encoded = uuid.to_bencode().unwrap();
+        assert_eq!(&encoded[..3], b"16:");
+        assert_eq!(&encoded[3..], uuid.as_bytes());
+        assert_eq!(uuid_::Uuid::from_bencode(&encoded).unwrap(), uuid);
+
+        let error = uuid_::Uuid::from_bencode(b"3:abc").unwrap_err();
+        assert_eq!(error.expected(), Some("a byte string of 16 bytes"));
+        assert_eq!(error.found(), Some("a byte string of 3 bytes"));
+        assert!(uuid_::Uuid::from_bencode(b"i1e").is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn c_strings_should_round_trip_without_terminator() {
+        use crate::encoding::ToBencode;
+
+        let string = CString::new("hello").unwrap();
+        let encoded = string.to_bencode().unwrap();
+        assert_eq!(encoded, b"5:hello");
+        assert_eq!(string.as_c_str().to_bencode().unwrap(), encoded);
+        assert_eq!(CString::from_bencode(&encoded).unwrap(), string);
+
+        let error = CString::from_bencode(b"3:a\0b").unwrap_err();
+        assert!(error.to_string().contains("nul byte"));
+    }
+
     #[test]
     #[should_panic(expected = "Num")]
     fn from_bencode_to_as_string_should_fail_for_integer() {
@@ -213,4 +991,85 @@ mod test {
     fn from_bencode_to_as_string_should_fail_for_dictionary() {
         AsString::<Vec<u8>>::from_bencode(&b"d1:a1:ae"[..]).unwrap();
     }
+
+    #[test]
+    fn saturating_integers_should_clamp_to_their_bounds() {
+        assert_eq!(
+            Saturating::<u8>::from_bencode(b"i255e").unwrap(),
+            Saturating(255)
+        );
+        assert_eq!(
+            Saturating::<u8>::from_bencode(b"i256e").unwrap(),
+            Saturating(255)
+        );
+        assert_eq!(
+            Saturating::<u8>::from_bencode(b"i-256e").unwrap(),
+            Saturating(0)
+        );
+        assert_eq!(
+            Saturating::<i8>::from_bencode(b"i-129e").unwrap(),
+            Saturating(-128)
+        );
+        assert_eq!(
+            Saturating::<i128>::from_bencode(b"i999999999999999999999999999999999999999999e")
+                .unwrap(),
+            Saturating(i128::max_value())
+        );
+    }
+
+    #[test]
+    fn saturating_integers_should_reject_invalid_input() {
+        assert!(Saturating::<u8>::from_bencode(b"i-0e").is_err());
+        assert!(Saturating::<u8>::from_bencode(b"3:256").is_err());
+    }
+
+    #[rustversion::since(1.51)]
+    #[test]
+    fn byte_arrays_should_decode_from_strings_of_their_length() {
+        assert_eq!(<[u8; 3]>::from_bencode(b"3:abc").unwrap(), *b"abc");
+        assert_eq!(<[u8; 0]>::from_bencode(b"0:").unwrap(), [0; 0]);
+
+        let error = <[u8; 3]>::from_bencode(b"4:abcd").unwrap_err();
+        assert_eq!(error.expected(), Some("a byte string of 3 bytes"));
+        assert_eq!(error.found(), Some("a byte string of 4 bytes"));
+        assert!(<[u8; 3]>::from_bencode(b"i1e").is_err());
+    }
+
+    #[rustversion::since(1.51)]
+    #[test]
+    fn maps_with_byte_array_keys_should_round_trip() {
+        use crate::encoding::ToBencode;
+
+        let encoded = b"d4:\x00\x00\x00\x01i1e4:\xff\xff\xff\xffi2ee";
+        let map = BTreeMap::<[u8; 4], i64>::from_bencode(encoded).unwrap();
+
+        assert_eq!(map[&[0, 0, 0, 1]], 1);
+        assert_eq!(map[&[0xff; 4]], 2);
+        assert_eq!(map.to_bencode().unwrap(), &encoded[..]);
+        assert!(BTreeMap::<[u8; 4], i64>::from_bencode(b"d3:abci1ee").is_err());
+    }
+
+    #[test]
+    fn integers_should_parse_like_str_parse() {
+        fn check<T>(content: &str)
+        where
+            T: FromBencode + core::str::FromStr + PartialEq + core::fmt::Debug,
+        {
+            let decoded = T::decode_bencode_object(Object::Integer(content)).ok();
+            assert_eq!(decoded, content.parse::<T>().ok(), "{}", content);
+        }
+
+        let contents = "0 -0 +1 1 -1 007 - 1a 1: 1/ 255 256 -128 -129 \
+            9999999999999999999 999999999999999999a -99999999999999999~9 \
+            9223372036854775807 9223372036854775808 -9223372036854775808 -9223372036854775809 \
+            18446744073709551615 18446744073709551616 99999999999999999999 100000000000000000000";
+        for content in contents.split(' ').chain(Some("")) {
+            check::<u8>(content);
+            check::<i8>(content);
+            check::<u64>(content);
+            check::<i64>(content);
+            check::<usize>(content);
+            check::<i128>(content);
+        }
+    }
 }
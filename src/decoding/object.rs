@@ -1,6 +1,10 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
 use crate::{
-    decoding::{DictDecoder, Error, ListDecoder},
+    decoding::{DictDecoder, Error, FromBencode, FromBencodeTuple, ListDecoder},
     state_tracker::Token,
+    value::Value,
 };
 
 /// An object read from a decoder
@@ -391,4 +395,118 @@ impl<'obj, 'ser: 'obj> Object<'obj, 'ser> {
     pub fn try_into_dictionary(self) -> Result<DictDecoder<'obj, 'ser>, Error> {
         self.dictionary_or_else(|obj| Err(Error::unexpected_token("Dict", obj.into_token().name())))
     }
+
+    /// Try to decode a list with exactly as many items as the tuple `T` has elements, decoding
+    /// each item as the type of the corresponding element. This is convenient for fixed-shape
+    /// lists such as coordinates, which would otherwise be read item by item.
+    ///
+    /// Lists of any other length are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bendy::decoding::Decoder;
+    ///
+    /// let mut decoder = Decoder::new(b"li3ei-4e6:origineli3ee");
+    ///
+    /// let object = decoder.next_object().unwrap().unwrap();
+    /// let point: (i32, i32, String) = object.try_into_tuple().unwrap();
+    /// assert_eq!(point, (3, -4, String::from("origin")));
+    ///
+    /// let too_short = decoder.next_object().unwrap().unwrap().try_into_tuple::<(u8, u8)>();
+    /// assert!(too_short.is_err());
+    /// ```
+    pub fn try_into_tuple<T: FromBencodeTuple>(self) -> Result<T, Error> {
+        T::decode_tuple(self.try_into_list()?)
+    }
+
+    /// Try to decode a list that starts with a fixed schema and continues with a variable tail,
+    /// e.g. a versioned format where new fields are appended over time. The first items are
+    /// decoded into the tuple `T` like [`Object::try_into_tuple`] does, and the rest are
+    /// returned as [`Value`]s.
+    ///
+    /// Lists with fewer items than `T` has elements are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bendy::{decoding::Decoder, value::Value};
+    ///
+    /// let mut decoder = Decoder::new(b"li2e4:nameli1eee");
+    ///
+    /// let object = decoder.next_object().unwrap().unwrap();
+    /// let (prefix, rest): ((u32, String), _) = object.try_into_prefixed_list().unwrap();
+    /// assert_eq!(prefix, (2, String::from("name")));
+    /// assert_eq!(rest, [Value::List(vec![Value::Integer(1)])]);
+    /// ```
+    pub fn try_into_prefixed_list<T: FromBencodeTuple>(
+        self,
+    ) -> Result<(T, Vec<Value<'ser>>), Error> {
+        let mut list = self.try_into_list()?;
+        let prefix = T::decode_prefix(&mut list)?;
+
+        let mut rest = Vec::new();
+        while let Some(item) = list.next_object()? {
+            rest.push(Value::decode_bencode_object(item)?);
+        }
+
+        Ok((prefix, rest))
+    }
+
+    /// Decode an enum that is encoded as a two-element list of an integer tag identifying the
+    /// variant and the variant's payload, e.g. `li1e3:fooe` for the variant with tag 1.
+    ///
+    /// The tag and the payload object are passed to `decode_variant`, which decodes the payload
+    /// according to the tag. Lists of any other length and non-integer tags are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bendy::decoding::{Decoder, Error, FromBencode, Object};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Shape {
+    ///     Circle(u32),
+    ///     Label(String),
+    /// }
+    ///
+    /// impl FromBencode for Shape {
+    ///     fn decode_bencode_object(object: Object) -> Result<Self, Error> {
+    ///         object.decode_int_tagged_enum(|tag, payload| match tag {
+    ///             0 => u32::decode_bencode_object(payload).map(Shape::Circle),
+    ///             1 => String::decode_bencode_object(payload).map(Shape::Label),
+    ///             _ => Err(Error::unexpected_token("tag 0 or 1", tag)),
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Shape::from_bencode(b"li0ei5ee").unwrap(), Shape::Circle(5));
+    /// assert_eq!(Shape::from_bencode(b"li1e2:hie").unwrap(), Shape::Label("hi".into()));
+    /// assert!(Shape::from_bencode(b"li2ei5ee").is_err());
+    /// ```
+    pub fn decode_int_tagged_enum<T>(
+        self,
+        decode_variant: impl FnOnce(i64, Object<'_, 'ser>) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut list = self.try_into_list()?;
+
+        let tag = match list.next_object()? {
+            Some(tag) => tag.try_into_integer()?.parse::<i64>()?,
+            None => return Err(Error::unexpected_token("Num", Token::End.name())),
+        };
+
+        let value = match list.next_object()? {
+            Some(payload) => decode_variant(tag, payload)?,
+            None => return Err(Error::unexpected_token("a payload", Token::End.name())),
+        };
+
+        if let Some(extra) = list.next_object()? {
+            return Err(Error::unexpected_token(
+                Token::End.name(),
+                extra.into_token().name(),
+            ));
+        }
+
+        Ok(value)
+    }
 }
@@ -0,0 +1,85 @@
+use alloc::vec::Vec;
+
+use crate::{
+    decoding::{Error, FromBencode, Object},
+    encoding::{self, SingleItemEncoder, ToBencode},
+    value::Value,
+};
+
+/// A dictionary that keeps its pairs in the order they appear on the wire, instead of
+/// collecting them into a map.
+///
+/// Decoding rejects input whose keys are not strictly sorted, so a successfully decoded
+/// `OrderedDict` holds its pairs in canonical order. Encoding writes the pairs in the stored
+/// order and fails if they are not sorted, which makes this suitable for byte-exact
+/// transformations of a dictionary.
+///
+/// ```
+/// # use bendy::{decoding::{FromBencode, OrderedDict}, encoding::ToBencode, value::Value};
+/// #
+/// let dict = OrderedDict::from_bencode(b"d1:ai1e1:bi2ee").unwrap();
+/// assert_eq!(dict.0[0], (b"a".to_vec(), Value::Integer(1)));
+/// assert_eq!(dict.to_bencode().unwrap(), b"d1:ai1e1:bi2ee");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OrderedDict(pub Vec<(Vec<u8>, Value<'static>)>);
+
+impl ToBencode for OrderedDict {
+    const MAX_DEPTH: usize = <Value as ToBencode>::MAX_DEPTH + 1;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), encoding::Error> {
+        encoder.emit_dict(|mut e| {
+            for (key, value) in &self.0 {
+                e.emit_pair(key, value)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl FromBencode for OrderedDict {
+    const EXPECTED_RECURSION_DEPTH: usize = <Value as FromBencode>::EXPECTED_RECURSION_DEPTH + 1;
+
+    fn decode_bencode_object(object: Object) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut dict = object.try_into_dictionary()?;
+        let mut pairs = Vec::new();
+
+        while let Some((key, value)) = dict.next_pair()? {
+            pairs.push((key.to_vec(), Value::decode_bencode_object(value)?));
+        }
+
+        Ok(OrderedDict(pairs))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use alloc::vec;
+
+    #[test]
+    fn decoding_should_keep_wire_order() {
+        let dict = OrderedDict::from_bencode(b"d3:bari1e3:fooli2eee").unwrap();
+        assert_eq!(
+            dict.0,
+            vec![
+                (b"bar".to_vec(), Value::Integer(1)),
+                (b"foo".to_vec(), Value::List(vec![Value::Integer(2)])),
+            ]
+        );
+        assert_eq!(dict.to_bencode().unwrap(), b"d3:bari1e3:fooli2eee");
+    }
+
+    #[test]
+    fn encoding_should_reject_unsorted_pairs() {
+        let dict = OrderedDict(vec![
+            (b"foo".to_vec(), Value::Integer(1)),
+            (b"bar".to_vec(), Value::Integer(2)),
+        ]);
+        assert!(dict.to_bencode().is_err());
+    }
+}
@@ -102,6 +102,75 @@
 //! # }
 //! ```
 //!
+//! # Recursive types
+//!
+//! Recursive types, e.g. linked lists or trees built from `Option<Box<Node>>`, follow the same
+//! convention: they declare a [`ToBencode::MAX_DEPTH`] (and
+//! [`FromBencode::EXPECTED_RECURSION_DEPTH`]) of 0 and are encoded and decoded with an explicit
+//! limit through [`ToBencode::to_bencode_with_max_depth`] and
+//! [`FromBencode::from_bencode_with_max_depth`]. The limit also bounds the stack space used by
+//! the recursion, so it should be chosen tightly when decoding untrusted input.
+//!
+//! ```
+//! use bendy::{
+//!     decoding::{Error as DecodingError, FromBencode, Object},
+//!     encoding::{Error, SingleItemEncoder, ToBencode},
+//! };
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Node {
+//!     value: i64,
+//!     next: Option<Box<Node>>,
+//! }
+//!
+//! impl ToBencode for Node {
+//!     const MAX_DEPTH: usize = 0;
+//!
+//!     fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+//!         encoder.emit_dict(|mut e| {
+//!             e.emit_pair_if_some(b"next", &self.next)?;
+//!             e.emit_pair(b"value", self.value)
+//!         })
+//!     }
+//! }
+//!
+//! impl FromBencode for Node {
+//!     const EXPECTED_RECURSION_DEPTH: usize = 0;
+//!
+//!     fn decode_bencode_object(object: Object) -> Result<Self, DecodingError> {
+//!         let mut dict = object.try_into_dictionary()?;
+//!         let mut value = None;
+//!         let mut next = None;
+//!
+//!         while let Some((key, object)) = dict.next_pair()? {
+//!             match key {
+//!                 b"next" => next = Some(Box::decode_bencode_object(object)?),
+//!                 b"value" => value = Some(i64::decode_bencode_object(object)?),
+//!                 _ => return Err(DecodingError::unexpected_field(String::from_utf8_lossy(key))),
+//!             }
+//!         }
+//!
+//!         let value = value.ok_or_else(|| DecodingError::missing_field("value"))?;
+//!         Ok(Node { value, next })
+//!     }
+//! }
+//!
+//! let list = Node {
+//!     value: 1,
+//!     next: Some(Box::new(Node { value: 2, next: None })),
+//! };
+//!
+//! let encoded = list.to_bencode_with_max_depth(16).unwrap();
+//! assert_eq!(encoded, b"d4:nextd5:valuei2ee5:valuei1ee");
+//! assert_eq!(Node::from_bencode_with_max_depth(&encoded, 16).unwrap(), list);
+//!
+//! // Without an explicit limit, the declared depth of 0 rejects any dict
+//! assert!(list.to_bencode().is_err());
+//! ```
+//!
+//! [`FromBencode::EXPECTED_RECURSION_DEPTH`]: crate::decoding::FromBencode::EXPECTED_RECURSION_DEPTH
+//! [`FromBencode::from_bencode_with_max_depth`]: crate::decoding::FromBencode::from_bencode_with_max_depth
+//!
 //! # Error handling
 //!
 //! Once an error occurs during encoding, all future calls to the same encoding stream will fail
@@ -116,14 +185,23 @@
 //! [`UnsortedKeys`]: self::Error#UnsortedKeys
 //! [`NestingTooDeep`]: self::Error#NestingTooDeep
 
+mod dict_key;
 mod encoder;
 mod error;
 mod printable_integer;
+#[cfg(feature = "test-util")]
+mod test_util;
 mod to_bencode;
 
 pub use self::{
+    dict_key::DictKey,
     encoder::{Encoder, SingleItemEncoder, SortedDictEncoder, UnsortedDictEncoder},
     error::Error,
     printable_integer::PrintableInteger,
-    to_bencode::{AsString, ToBencode},
+    to_bencode::{AsString, DynToBencode, Saturating, ToBencode},
 };
+
+#[cfg(feature = "test-util")]
+pub use self::test_util::{assert_canonical, roundtrip_ok};
+#[cfg(feature = "std")]
+pub use self::to_bencode::{Millis, Seconds};
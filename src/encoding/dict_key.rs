@@ -0,0 +1,62 @@
+#[cfg(not(feature = "std"))]
+use alloc::{borrow::Cow, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+/// A key accepted by the `emit_pair` methods of the dict encoders: a string or a byte string,
+/// borrowed, owned or in a `Cow`, a byte string literal, or a reference to any of these.
+///
+/// This trait is sealed, so it can't be implemented outside of bendy.
+pub trait DictKey: private::Sealed {
+    /// The bytes the key is encoded as
+    #[doc(hidden)]
+    fn key_bytes(&self) -> &[u8];
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_dict_key {
+    ($($type:ty),* => |$key:ident| $bytes:expr) => {$(
+        impl private::Sealed for $type {}
+
+        impl DictKey for $type {
+            fn key_bytes(&self) -> &[u8] {
+                let $key = self;
+                $bytes
+            }
+        }
+    )*}
+}
+
+impl_dict_key!(str, String, Cow<'_, str> => |key| key.as_bytes());
+impl_dict_key!([u8] => |key| key);
+impl_dict_key!(Vec<u8>, Cow<'_, [u8]> => |key| &key[..]);
+
+#[rustversion::since(1.51)]
+impl<const N: usize> private::Sealed for [u8; N] {}
+
+#[rustversion::since(1.51)]
+impl<const N: usize> DictKey for [u8; N] {
+    fn key_bytes(&self) -> &[u8] {
+        &self[..]
+    }
+}
+
+// Before const generics, arrays are supported up to the same length as by the standard library
+#[rustversion::before(1.51)]
+impl_dict_key!(
+    [u8; 0], [u8; 1], [u8; 2], [u8; 3], [u8; 4], [u8; 5], [u8; 6], [u8; 7], [u8; 8], [u8; 9],
+    [u8; 10], [u8; 11], [u8; 12], [u8; 13], [u8; 14], [u8; 15], [u8; 16], [u8; 17], [u8; 18],
+    [u8; 19], [u8; 20], [u8; 21], [u8; 22], [u8; 23], [u8; 24], [u8; 25], [u8; 26], [u8; 27],
+    [u8; 28], [u8; 29], [u8; 30], [u8; 31], [u8; 32] => |key| &key[..]
+);
+
+impl<T: DictKey + ?Sized> private::Sealed for &T {}
+
+impl<T: DictKey + ?Sized> DictKey for &T {
+    fn key_bytes(&self) -> &[u8] {
+        (**self).key_bytes()
+    }
+}
@@ -7,10 +7,15 @@ use alloc::{
     vec::Vec,
 };
 #[cfg(feature = "std")]
-use std::{collections::BTreeMap, vec::Vec};
+use std::{
+    collections::BTreeMap,
+    io::{self, Read},
+    vec::Vec,
+};
 
 use crate::{
-    encoding::{Error, PrintableInteger, ToBencode},
+    decoding::Decoder,
+    encoding::{DictKey, Error, PrintableInteger, ToBencode},
     state_tracker::{StateTracker, StructureError, Token},
 };
 
@@ -101,7 +106,7 @@ impl Encoder {
         // possible (for performance)
         self.state.observe_token(&Token::Num(""))?;
         self.output.push(b'i');
-        self.output.extend_from_slice(value.to_string().as_bytes());
+        value.write_decimal(&mut self.output);
         self.output.push(b'e');
         Ok(())
     }
@@ -111,9 +116,114 @@ impl Encoder {
         self.emit_token(Token::String(value.as_bytes()))
     }
 
-    /// Emit a byte array
-    pub fn emit_bytes(&mut self, value: &[u8]) -> Result<(), Error> {
-        self.emit_token(Token::String(value))
+    /// Emit a byte array. Anything that can be viewed as a byte string may be passed, e.g.
+    /// `&[u8]`, `&[u8; N]` or `&Vec<u8>`.
+    pub fn emit_bytes<B: AsRef<[u8]>>(&mut self, value: B) -> Result<(), Error> {
+        self.emit_token(Token::String(value.as_ref()))
+    }
+
+    /// Emit a byte string of `len` bytes read from `reader`, e.g. the contents of a file,
+    /// without first reading it into a buffer of its own.
+    ///
+    /// Exactly `len` bytes are read. If `reader` fails or ends before yielding them, this
+    /// returns a [`MalformedContent`](Error::MalformedContent) error wrapping the `io::Error`.
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_bytes_from_reader(3, &b"foobar"[..])?;
+    ///
+    /// assert_eq!(encoder.get_output()?, b"3:foo");
+    /// assert!(Encoder::new().emit_bytes_from_reader(7, &b"foobar"[..]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn emit_bytes_from_reader<R: Read>(&mut self, len: usize, reader: R) -> Result<(), Error> {
+        self.state.check_error()?;
+
+        let start = self.output.len();
+        len.write_decimal(&mut self.output);
+        self.output.push(b':');
+        let content_start = self.output.len();
+
+        let result = match reader.take(len as u64).read_to_end(&mut self.output) {
+            Ok(read) if read == len => Ok(()),
+            Ok(read) => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("the reader ended after {} of {} bytes", read, len),
+            )),
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            self.output.truncate(start);
+            return self.state.latch_err(Err(Error::malformed_content(error)));
+        }
+
+        // The content is only known now, but is needed to check the order of dict keys
+        self.state
+            .observe_token(&Token::String(&self.output[content_start..]))
+    }
+
+    /// Emit an already encoded object verbatim, e.g. a value that is passed through unchanged
+    /// from [`DictDecoder::into_raw`] or [`ListDecoder::into_raw`].
+    ///
+    /// `raw` must contain exactly one object in canonical encoding. It is checked like any other
+    /// emitted value: its nesting counts towards the maximum depth, and as a dict key it must
+    /// keep the keys of the surrounding dict sorted.
+    ///
+    /// [`DictDecoder::into_raw`]: crate::decoding::DictDecoder::into_raw
+    /// [`ListDecoder::into_raw`]: crate::decoding::ListDecoder::into_raw
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_list(|e| {
+    ///     e.emit_raw(b"d1:ai1ee")?;
+    ///     e.emit_int(2)
+    /// })?;
+    ///
+    /// assert_eq!(encoder.get_output()?, b"ld1:ai1eei2ee");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_raw(&mut self, raw: &[u8]) -> Result<(), Error> {
+        self.state.check_error()?;
+
+        let mut depth = 0usize;
+        let mut complete = false;
+        for token in Decoder::new_unlimited(raw).tokens() {
+            let token = match token {
+                Ok(token) => token,
+                Err(error) => return self.state.latch_err(Err(Error::malformed_content(error))),
+            };
+            if complete {
+                return self
+                    .state
+                    .latch_err(Err(Error::from(StructureError::TrailingData)));
+            }
+
+            self.state.observe_token(&token)?;
+            match token {
+                Token::List | Token::Dict => depth += 1,
+                Token::End => depth -= 1,
+                Token::String(_) | Token::Num(_) => {},
+            }
+            complete = depth == 0;
+        }
+
+        if !complete {
+            return self
+                .state
+                .latch_err(Err(Error::from(StructureError::UnexpectedEof)));
+        }
+
+        self.output.extend_from_slice(raw);
+        Ok(())
     }
 
     /// Emit a dictionary where you know that the keys are already
@@ -170,6 +280,33 @@ impl Encoder {
         self.emit_token(Token::End)
     }
 
+    /// Emit an optional value as a list: `None` becomes the empty list and `Some(value)` becomes
+    /// a list containing only `value`. This keeps `None` distinguishable from any value, at the
+    /// cost of the wrapping list. To leave a dict key out instead, see
+    /// [`SortedDictEncoder::emit_pair_if_some`].
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_option(&Some(1))?;
+    /// encoder.emit_option::<i64>(&None)?;
+    /// encoder.emit_dict(|mut e| e.emit_pair_with(b"a", |e| e.emit_option(&Some(2))))?;
+    ///
+    /// assert_eq!(encoder.get_output()?, b"li1eeled1:ali2eee");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_option<T: ToBencode>(&mut self, opt: &Option<T>) -> Result<(), Error> {
+        self.emit_list(|e| {
+            if let Some(value) = opt {
+                e.emit(value)?;
+            }
+            Ok(())
+        })
+    }
+
     /// Emit a dictionary that may have keys out of order. This will write the dict
     /// values to temporary memory, then sort them before adding them to the serialized
     /// stream
@@ -199,12 +336,197 @@ impl Encoder {
         self.end_unsorted_dict(encoder)
     }
 
+    /// Emit a dictionary from a list of key/value pairs in arbitrary order. The pairs are sorted
+    /// by key in place, and the values are then encoded directly into the output in that order.
+    ///
+    /// Unlike [`emit_and_sort_dict`], this doesn't buffer each encoded value in temporary memory.
+    /// Duplicate keys are reported as an error before anything is written.
+    ///
+    /// [`emit_and_sort_dict`]: Encoder::emit_and_sort_dict
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut pairs = vec![(b"b".to_vec(), 2), (b"a".to_vec(), 1)];
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_dict_sorted_checked(&mut pairs)?;
+    ///
+    /// assert_eq!(encoder.get_output()?, b"d1:ai1e1:bi2ee");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_dict_sorted_checked<K, V>(&mut self, pairs: &mut [(K, V)]) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: ToBencode,
+    {
+        self.state.check_error()?;
+
+        pairs.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+        if let Some(window) = pairs
+            .windows(2)
+            .find(|window| window[0].0.as_ref() == window[1].0.as_ref())
+        {
+            let error = StructureError::InvalidState {
+                state: format!(
+                    "Duplicate key {}",
+                    String::from_utf8_lossy(window[0].0.as_ref())
+                ),
+            };
+            return self.state.latch_err(Err(Error::from(error)));
+        }
+
+        self.emit_dict(|mut e| {
+            for (key, value) in pairs.iter() {
+                e.emit_pair(key.as_ref(), value)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Emit a dictionary with a fixed set of keys that are known at the call site, e.g. the
+    /// fields of a small schema. The pairs must already be sorted by key, so they are written
+    /// directly without allocating.
+    ///
+    /// In debug builds, a misordered or duplicated key trips an assertion, which catches
+    /// misordered literals early. In release builds, the encoder reports an error instead.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_dict_fixed(&[(b"id", 7), (b"port", 6881)])?;
+    ///
+    /// assert_eq!(encoder.get_output()?, b"d2:idi7e4:porti6881ee");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_dict_fixed<V: ToBencode>(
+        &mut self,
+        pairs: &[(&'static [u8], V)],
+    ) -> Result<(), Error> {
+        debug_assert!(
+            pairs.windows(2).all(|window| window[0].0 < window[1].0),
+            "keys of a fixed dict must be strictly sorted"
+        );
+
+        self.emit_dict(|mut e| {
+            for (key, value) in pairs {
+                e.emit_pair(key, value)?;
+            }
+            Ok(())
+        })
+    }
+
     /// Return the encoded string, if all objects written are complete
     pub fn get_output(mut self) -> Result<Vec<u8>, Error> {
         self.state.observe_eof()?;
         Ok(self.output)
     }
 
+    /// Return the encoded string, if all objects written are complete, and reset the encoder so
+    /// it can be used for the next message.
+    ///
+    /// The encoder keeps its max depth. As the returned buffer is handed over to the caller,
+    /// each call allocates a new output buffer, pre-sized to the length of the message just
+    /// returned. Use [`take_output_into`](Self::take_output_into) to recycle buffers instead.
+    ///
+    /// If the output is incomplete or an error was latched, the error is returned and the
+    /// encoder is left untouched: an incomplete message can still be finished, and
+    /// [`reset`](Self::reset) discards it.
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut encoder = Encoder::new();
+    ///
+    /// encoder.emit_int(1)?;
+    /// assert_eq!(encoder.take_output()?, b"i1e");
+    ///
+    /// encoder.emit_str("two")?;
+    /// assert_eq!(encoder.take_output()?, b"3:two");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn take_output(&mut self) -> Result<Vec<u8>, Error> {
+        let mut output = Vec::with_capacity(self.output.len());
+        self.take_output_into(&mut output)?;
+        Ok(output)
+    }
+
+    /// Move the encoded string into `output`, if all objects written are complete, and reset the
+    /// encoder so it can be used for the next message.
+    ///
+    /// The buffers are swapped: the encoder continues with the cleared previous content of
+    /// `output`, keeping its capacity. Passing the same buffer for every message thus stops
+    /// allocating once both buffers have grown to the size of the messages. Errors are handled
+    /// like in [`take_output`](Self::take_output), leaving `output` untouched.
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut encoder = Encoder::new();
+    /// let mut message = Vec::new();
+    ///
+    /// for value in &[1, 22, 333] {
+    ///     encoder.emit_int(*value)?;
+    ///     encoder.take_output_into(&mut message)?;
+    ///     assert_eq!(message, format!("i{}e", value).as_bytes());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn take_output_into(&mut self, output: &mut Vec<u8>) -> Result<(), Error> {
+        // Unlike `get_output`, don't latch an error for incomplete output, as the encoder is kept
+        self.state.check_error()?;
+        if self.state.depth() != 0 {
+            return Err(Error::from(StructureError::UnexpectedEof));
+        }
+
+        output.clear();
+        core::mem::swap(&mut self.output, output);
+        self.state.reset();
+        Ok(())
+    }
+
+    /// Discard everything written so far, including any latched error, so the encoder can be
+    /// reused. The output buffer keeps its capacity and the max depth is retained.
+    pub fn reset(&mut self) {
+        self.output.clear();
+        self.state.reset();
+    }
+
+    /// Return the encoded string as a `String`, if all objects written are complete and every
+    /// byte string emitted was valid UTF-8. The output is validated once, in a single pass.
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_list(|e| {
+    ///     e.emit_str("name")?;
+    ///     e.emit_int(1)
+    /// })?;
+    ///
+    /// assert_eq!(encoder.get_output_string()?, "l4:namei1ee");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_output_string(self) -> Result<String, Error> {
+        let output = self.get_output()?;
+        String::from_utf8(output).map_err(Error::malformed_content)
+    }
+
     pub(crate) fn begin_unsorted_dict(&mut self) -> Result<UnsortedDictEncoder, Error> {
         // emit the dict token so that a pre-existing state error is reported early
         self.emit_token(Token::Dict)?;
@@ -226,6 +548,19 @@ impl Encoder {
 
         Ok(())
     }
+
+    /// Emit a list of integers, formatting them straight into the output
+    pub(crate) fn emit_int_list<T: PrintableInteger>(&mut self, values: &[T]) -> Result<(), Error> {
+        self.emit_token(Token::List)?;
+        // An integer is always a valid list item and doesn't change the state of the list, so
+        // the items don't need to be observed one by one
+        for value in values {
+            self.output.push(b'i');
+            value.write_decimal(&mut self.output);
+            self.output.push(b'e');
+        }
+        self.emit_token(Token::End)
+    }
 }
 
 /// An encoder that can only encode a single item. See [`Encoder`]
@@ -265,12 +600,25 @@ impl<'a> SingleItemEncoder<'a> {
         self.encoder.emit_str(value)
     }
 
-    /// Emit a byte array
-    pub fn emit_bytes(self, value: &[u8]) -> Result<(), Error> {
+    /// Emit a byte array. See [`Encoder::emit_bytes`].
+    pub fn emit_bytes<B: AsRef<[u8]>>(self, value: B) -> Result<(), Error> {
         *self.value_written = true;
         self.encoder.emit_bytes(value)
     }
 
+    /// Emit a byte string read from `reader`. See [`Encoder::emit_bytes_from_reader`].
+    #[cfg(feature = "std")]
+    pub fn emit_bytes_from_reader<R: Read>(self, len: usize, reader: R) -> Result<(), Error> {
+        *self.value_written = true;
+        self.encoder.emit_bytes_from_reader(len, reader)
+    }
+
+    /// Emit an already encoded object verbatim. See [`Encoder::emit_raw`].
+    pub fn emit_raw(self, raw: &[u8]) -> Result<(), Error> {
+        *self.value_written = true;
+        self.encoder.emit_raw(raw)
+    }
+
     /// Emit an arbitrary list
     pub fn emit_list<F>(self, list_cb: F) -> Result<(), Error>
     where
@@ -280,6 +628,18 @@ impl<'a> SingleItemEncoder<'a> {
         self.encoder.emit_list(list_cb)
     }
 
+    /// Emit an optional value as a list. See [`Encoder::emit_option`].
+    pub fn emit_option<T: ToBencode>(self, opt: &Option<T>) -> Result<(), Error> {
+        *self.value_written = true;
+        self.encoder.emit_option(opt)
+    }
+
+    /// Emit a list of integers. See [`Encoder::emit_int_list`].
+    pub(crate) fn emit_int_list<T: PrintableInteger>(self, values: &[T]) -> Result<(), Error> {
+        *self.value_written = true;
+        self.encoder.emit_int_list(values)
+    }
+
     /// Emit a sorted dictionary. If the input dictionary is unsorted, this will return an error.
     pub fn emit_dict<F>(self, content_cb: F) -> Result<(), Error>
     where
@@ -300,6 +660,23 @@ impl<'a> SingleItemEncoder<'a> {
         self.encoder.emit_and_sort_dict(content_cb)
     }
 
+    /// Emit a dictionary from a list of key/value pairs in arbitrary order, sorting the pairs in
+    /// place. See [`Encoder::emit_dict_sorted_checked`].
+    pub fn emit_dict_sorted_checked<K, V>(self, pairs: &mut [(K, V)]) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: ToBencode,
+    {
+        *self.value_written = true;
+        self.encoder.emit_dict_sorted_checked(pairs)
+    }
+
+    /// Emit a dictionary with a fixed set of pre-sorted keys. See [`Encoder::emit_dict_fixed`].
+    pub fn emit_dict_fixed<V: ToBencode>(self, pairs: &[(&'static [u8], V)]) -> Result<(), Error> {
+        *self.value_written = true;
+        self.encoder.emit_dict_fixed(pairs)
+    }
+
     /// Emit an arbitrary list.
     ///
     /// Attention: If this method is used while canonical output is required
@@ -315,6 +692,82 @@ impl<'a> SingleItemEncoder<'a> {
             Ok(())
         })
     }
+
+    /// Emit a list from an iterator whose items are produced fallibly. Encoding stops at the
+    /// first `Err` item, which is converted into an [`Error`] and returned.
+    ///
+    /// Like [`emit_unchecked_list`], the caller needs to ensure that the iterator has a defined
+    /// order if canonical output is required.
+    ///
+    /// [`emit_unchecked_list`]: SingleItemEncoder::emit_unchecked_list
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let items = vec!["1", "2", "3"].into_iter().map(|item| {
+    ///     item.parse::<u32>().map_err(Error::malformed_content)
+    /// });
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_with(|e| e.emit_list_try(items))?;
+    ///
+    /// assert_eq!(encoder.get_output()?, b"li1ei2ei3ee");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_list_try<I, T, E>(self, iter: I) -> Result<(), Error>
+    where
+        I: Iterator<Item = Result<T, E>>,
+        T: ToBencode,
+        E: Into<Error>,
+    {
+        self.emit_list(|e| {
+            for item in iter {
+                e.emit(item.map_err(Into::into)?)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Emit a list whose items are produced by a stateful generator, e.g. one that pages
+    /// through a cursor.
+    ///
+    /// `generate` is called repeatedly with the encoder for the list items, and returns whether
+    /// it should be called again. Each call may emit any number of items, typically one, and the
+    /// last call, which returns `false`, may emit items as well. Errors abort encoding.
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let pages = [&[1, 2][..], &[3]];
+    /// let mut page = 0;
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_with(|e| {
+    ///     e.emit_generated_list(|e| {
+    ///         for &item in pages[page] {
+    ///             e.emit_int(item)?;
+    ///         }
+    ///         page += 1;
+    ///         Ok(page < pages.len())
+    ///     })
+    /// })?;
+    ///
+    /// assert_eq!(encoder.get_output()?, b"li1ei2ei3ee");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_generated_list<F>(self, mut generate: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut Encoder) -> Result<bool, Error>,
+    {
+        self.emit_list(|e| {
+            while generate(e)? {}
+            Ok(())
+        })
+    }
 }
 
 /// Encodes a map with pre-sorted keys
@@ -323,24 +776,96 @@ pub struct SortedDictEncoder<'a> {
 }
 
 impl<'a> SortedDictEncoder<'a> {
-    /// Emit a key/value pair
-    pub fn emit_pair<E>(&mut self, key: &[u8], value: E) -> Result<(), Error>
+    /// Emit a key/value pair. The key may be a string or a byte string, borrowed, owned or in a
+    /// `Cow`, see [`DictKey`].
+    pub fn emit_pair<K, E>(&mut self, key: K, value: E) -> Result<(), Error>
     where
+        K: DictKey,
         E: ToBencode,
     {
-        self.encoder.emit_token(Token::String(key))?;
+        self.encoder.emit_token(Token::String(key.key_bytes()))?;
         self.encoder.emit(value)
     }
 
+    /// Emit a key/value pair if `value` is `Some`, and nothing at all if it is `None`. Unlike
+    /// [`Encoder::emit_option`], the value is written without a wrapping list, so absence of
+    /// the key is the only representation of `None`.
+    ///
+    /// ```
+    /// # use bendy::encoding::{Encoder, Error};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_dict(|mut e| {
+    ///     e.emit_pair_if_some(b"a", &Some(1))?;
+    ///     e.emit_pair_if_some::<_, i64>(b"b", &None)
+    /// })?;
+    ///
+    /// assert_eq!(encoder.get_output()?, b"d1:ai1ee");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_pair_if_some<K, T>(&mut self, key: K, value: &Option<T>) -> Result<(), Error>
+    where
+        K: DictKey,
+        T: ToBencode,
+    {
+        match value {
+            Some(value) => self.emit_pair(key, value),
+            None => Ok(()),
+        }
+    }
+
     /// Equivalent to [`SortedDictEncoder::emit_pair()`], but forces the type of the value
     /// to be a callback
-    pub fn emit_pair_with<F>(&mut self, key: &[u8], value_cb: F) -> Result<(), Error>
+    pub fn emit_pair_with<K, F>(&mut self, key: K, value_cb: F) -> Result<(), Error>
     where
+        K: DictKey,
         F: FnOnce(SingleItemEncoder) -> Result<(), Error>,
     {
-        self.encoder.emit_token(Token::String(key))?;
+        self.encoder.emit_token(Token::String(key.key_bytes()))?;
         self.encoder.emit_with(value_cb)
     }
+
+    /// Emit a key/value pair whose value is already encoded, see [`Encoder::emit_raw`]. Pairs
+    /// emitted with this method and with [`SortedDictEncoder::emit_pair()`] can be mixed freely,
+    /// and their keys are checked to be sorted across both.
+    ///
+    /// This allows modifying some values of a decoded dict while passing the others through
+    /// byte for byte, e.g. to preserve signatures over them:
+    ///
+    /// ```
+    /// # use bendy::{decoding::Decoder, encoding::{Encoder, Error}};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let mut decoder = Decoder::new(b"d3:agei41e4:infod1:xi1eee");
+    /// let object = decoder.next_object().map_err(Error::malformed_content)?;
+    /// let mut dict = object
+    ///     .unwrap()
+    ///     .try_into_dictionary()
+    ///     .map_err(Error::malformed_content)?;
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.emit_dict(|mut e| {
+    ///     while let Some((key, value)) = dict.next_pair().map_err(Error::malformed_content)? {
+    ///         if key == b"age" {
+    ///             e.emit_pair(key, 42)?;
+    ///         } else {
+    ///             let raw = value.try_into_dictionary().and_then(|d| d.into_raw());
+    ///             e.emit_pair_raw(key, raw.map_err(Error::malformed_content)?)?;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// })?;
+    ///
+    /// assert_eq!(encoder.get_output()?, b"d3:agei42e4:infod1:xi1eee");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_pair_raw<K: DictKey>(&mut self, key: K, raw_value: &[u8]) -> Result<(), Error> {
+        self.encoder.emit_token(Token::String(key.key_bytes()))?;
+        self.encoder.emit_raw(raw_value)
+    }
 }
 
 /// Helper to write a dictionary that may have keys out of order. This will buffer the
@@ -361,17 +886,20 @@ impl UnsortedDictEncoder {
         }
     }
 
-    /// Emit a key/value pair
-    pub fn emit_pair<E>(&mut self, key: &[u8], value: E) -> Result<(), Error>
+    /// Emit a key/value pair. The key may be a string or a byte string, borrowed, owned or in a
+    /// `Cow`, see [`DictKey`].
+    pub fn emit_pair<K, E>(&mut self, key: K, value: E) -> Result<(), Error>
     where
+        K: DictKey,
         E: ToBencode,
     {
         self.emit_pair_with(key, |e| value.encode(e))
     }
 
     /// Emit a key/value pair where the value is produced by a callback
-    pub fn emit_pair_with<F>(&mut self, key: &[u8], value_cb: F) -> Result<(), Error>
+    pub fn emit_pair_with<K, F>(&mut self, key: K, value_cb: F) -> Result<(), Error>
     where
+        K: DictKey,
         F: FnOnce(SingleItemEncoder) -> Result<(), Error>,
     {
         let mut value_written = false;
@@ -393,7 +921,7 @@ impl UnsortedDictEncoder {
                 state: "No value was emitted".to_owned(),
             }));
         } else {
-            self.error = encoder.state.observe_eof().map_err(Error::from);
+            self.error = encoder.state.observe_eof();
         }
 
         if self.error.is_err() {
@@ -404,7 +932,7 @@ impl UnsortedDictEncoder {
             .get_output()
             .expect("Any errors should have been caught by observe_eof");
 
-        self.save_pair(key, encoded_object)
+        self.save_pair(key.key_bytes(), encoded_object)
     }
 
     #[cfg(feature = "serde")]
@@ -454,6 +982,9 @@ impl UnsortedDictEncoder {
 mod test {
     use super::*;
 
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
     #[test]
     pub fn simple_encoding_works() {
         let mut encoder = Encoder::new();
@@ -476,9 +1007,292 @@ mod test {
         );
     }
 
+    #[test]
+    fn emit_bytes_accepts_byte_containers() {
+        let owned = b"c".to_vec();
+
+        let mut encoder = Encoder::new();
+        encoder
+            .emit_list(|e| {
+                e.emit_bytes(b"ab")?;
+                e.emit_bytes(&owned)?;
+                e.emit_bytes(&b"d"[..])?;
+                e.emit_with(|e| e.emit_bytes([b'e']))
+            })
+            .unwrap();
+
+        assert_eq!(encoder.get_output().unwrap(), b"l2:ab1:c1:d1:ee");
+    }
+
+    #[test]
+    fn emit_list_try_should_stop_at_first_error() {
+        let error = Error::from(StructureError::invalid_state("item failed"));
+
+        let mut encoder = Encoder::new();
+        let items = vec![Ok(1), Ok(2)]
+            .into_iter()
+            .map(|item| item.map_err(|()| error.clone()));
+        encoder.emit_with(|e| e.emit_list_try(items)).unwrap();
+        assert_eq!(encoder.get_output().unwrap(), b"li1ei2ee");
+
+        let mut encoder = Encoder::new();
+        let items = vec![Ok(1), Err(error.clone()), Ok(3)].into_iter();
+        assert_eq!(
+            encoder.emit_with(|e| e.emit_list_try(items)),
+            Err(error.clone())
+        );
+        // the error is latched
+        assert_eq!(encoder.emit_int(1), Err(error));
+    }
+
+    #[test]
+    fn output_string_should_require_utf8() {
+        let mut encoder = Encoder::new();
+        encoder.emit_str("föö").unwrap();
+        assert_eq!(encoder.get_output_string().unwrap(), "5:föö");
+
+        let mut encoder = Encoder::new();
+        encoder.emit_bytes(b"\xff").unwrap();
+        assert!(encoder.get_output_string().is_err());
+
+        let mut encoder = Encoder::new();
+        encoder.emit_token(Token::List).unwrap();
+        assert!(encoder.get_output_string().is_err());
+    }
+
+    #[test]
+    fn options_should_be_wrapped_or_omitted() {
+        let mut encoder = Encoder::new();
+        encoder
+            .emit_dict(|mut e| {
+                e.emit_pair_with(b"a", |e| e.emit_option(&Some("x")))?;
+                e.emit_pair_if_some(b"b", &Some(2))?;
+                e.emit_pair_if_some::<_, i64>(b"c", &None)?;
+                e.emit_pair_with(b"d", |e| e.emit_option::<i64>(&None))
+            })
+            .unwrap();
+
+        assert_eq!(encoder.get_output().unwrap(), b"d1:al1:xe1:bi2e1:dlee");
+    }
+
+    #[test]
+    fn dict_keys_accept_string_types() {
+        use alloc::borrow::Cow;
+
+        let mut encoder = Encoder::new();
+        encoder
+            .emit_dict(|mut e| {
+                e.emit_pair("a", 1)?;
+                e.emit_pair(String::from("b"), 2)?;
+                e.emit_pair(Cow::Borrowed(&b"c"[..]), 3)?;
+                e.emit_pair(&b"d"[..], 4)?;
+                e.emit_pair(b"e".to_vec(), 5)?;
+                e.emit_pair(b"f", 6)
+            })
+            .unwrap();
+        encoder
+            .emit_and_sort_dict(|e| {
+                let keys: Vec<Cow<str>> = vec![Cow::Owned("c".into()), Cow::Borrowed("b")];
+                for (key, value) in keys.iter().zip(&[3, 2]) {
+                    e.emit_pair(key, value)?;
+                }
+                e.emit_pair(Cow::Borrowed("a"), 1)
+            })
+            .unwrap();
+
+        assert_eq!(
+            &encoder.get_output().unwrap()[..],
+            &b"d1:ai1e1:bi2e1:ci3e1:di4e1:ei5e1:fi6eed1:ai1e1:bi2e1:ci3ee"[..]
+        );
+    }
+
+    #[test]
+    fn emit_dict_sorted_checked_rejects_duplicates() {
+        let mut encoder = Encoder::new();
+        let mut pairs = [("b", 1), ("a", 2), ("b", 3)];
+
+        assert!(encoder.emit_dict_sorted_checked(&mut pairs).is_err());
+        assert!(encoder.get_output().is_err());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "strictly sorted")]
+    fn emit_dict_fixed_asserts_sorted_keys() {
+        let mut encoder = Encoder::new();
+        let _ = encoder.emit_dict_fixed(&[(b"port", 6881), (b"id", 7)]);
+    }
+
     #[test]
     fn emit_cb_must_emit() {
         let mut encoder = Encoder::new();
         assert!(encoder.emit_with(|_| Ok(())).is_err());
     }
+
+    #[test]
+    fn emit_generated_list_should_stop_when_told() {
+        let mut remaining = 3;
+        let mut encoder = Encoder::new();
+        encoder
+            .emit_with(|e| {
+                e.emit_generated_list(|e| {
+                    if remaining == 0 {
+                        return Ok(false);
+                    }
+                    e.emit_int(remaining)?;
+                    remaining -= 1;
+                    Ok(true)
+                })
+            })
+            .unwrap();
+        assert_eq!(encoder.get_output().unwrap(), b"li3ei2ei1ee");
+
+        let mut encoder = Encoder::new();
+        let result = encoder.emit_with(|e| {
+            e.emit_generated_list(|_| Err(Error::from(StructureError::invalid_state("cursor"))))
+        });
+        assert!(result.is_err());
+        assert!(encoder.get_output().is_err());
+    }
+
+    #[test]
+    fn raw_pairs_should_be_checked_for_order_with_other_pairs() {
+        let mut encoder = Encoder::new();
+        encoder
+            .emit_dict(|mut e| {
+                e.emit_pair_raw(b"a", b"li1ei2ee")?;
+                e.emit_pair(b"b", 3)?;
+                e.emit_pair_raw(b"c", b"0:")
+            })
+            .unwrap();
+        assert_eq!(encoder.get_output().unwrap(), b"d1:ali1ei2ee1:bi3e1:c0:e");
+
+        let mut encoder = Encoder::new();
+        let result = encoder.emit_dict(|mut e| {
+            e.emit_pair(b"b", 3)?;
+            e.emit_pair_raw(b"a", b"i1e")
+        });
+        assert_eq!(result, Err(Error::from(StructureError::UnsortedKeys)));
+    }
+
+    #[test]
+    fn raw_values_must_be_single_canonical_objects() {
+        for raw in &[&b""[..], b"i1ei2e", b"li1e", b"i01e", b"d1:bi1e1:ai2ee"] {
+            let mut encoder = Encoder::new();
+            assert!(encoder.emit_raw(raw).is_err(), "{:?}", raw);
+            assert!(encoder.get_output().is_err());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn bytes_from_readers_should_be_read_to_the_declared_length() {
+        let mut encoder = Encoder::new();
+        encoder
+            .emit_dict(|mut e| {
+                e.emit_pair_with(b"a", |e| e.emit_bytes_from_reader(2, &b"xyz"[..]))?;
+                e.emit_pair_with(b"b", |e| e.emit_bytes_from_reader(0, io::empty()))
+            })
+            .unwrap();
+        assert_eq!(encoder.get_output().unwrap(), b"d1:a2:xy1:b0:e");
+
+        let mut encoder = Encoder::new();
+        let result = encoder.emit_list(|e| e.emit_bytes_from_reader(4, &b"xyz"[..]));
+        match result {
+            Err(Error::MalformedContent { .. }) => {},
+            other => panic!("expected a malformed content error, got {:?}", other),
+        }
+        assert!(encoder.get_output().is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn reader_errors_should_be_reported() {
+        struct Failing;
+
+        impl Read for Failing {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "disk on fire"))
+            }
+        }
+
+        let error = Encoder::new()
+            .emit_bytes_from_reader(1, Failing)
+            .unwrap_err();
+        assert!(error.to_string().contains("disk on fire"));
+    }
+
+    #[test]
+    fn raw_values_should_count_towards_max_depth() {
+        let mut encoder = Encoder::new().with_max_depth(2);
+        let result = encoder.emit_list(|e| e.emit_raw(b"lli1eee"));
+
+        let error = StructureError::NestingTooDeep {
+            depth: 3,
+            max_depth: 2,
+        };
+        assert_eq!(result, Err(Error::from(error)));
+    }
+
+    #[test]
+    fn take_output_should_reset_for_the_next_message() {
+        let mut encoder = Encoder::new().with_max_depth(1);
+        encoder.emit_list(|e| e.emit_int(1)).unwrap();
+        assert_eq!(encoder.take_output().unwrap(), b"li1ee");
+        assert!(encoder.output.capacity() >= 5);
+
+        encoder.emit_str("a").unwrap();
+        assert_eq!(encoder.take_output().unwrap(), b"1:a");
+
+        let error = StructureError::NestingTooDeep {
+            depth: 2,
+            max_depth: 1,
+        };
+        let result = encoder.emit_list(|e| e.emit_list(|_| Ok(())));
+        assert_eq!(result, Err(Error::from(error)));
+    }
+
+    #[test]
+    fn take_output_should_not_reset_incomplete_output() {
+        let mut encoder = Encoder::new();
+        encoder.emit_token(Token::List).unwrap();
+        let error = Error::from(StructureError::UnexpectedEof);
+        assert_eq!(encoder.take_output(), Err(error));
+
+        encoder.emit_int(1).unwrap();
+        encoder.emit_token(Token::End).unwrap();
+        assert_eq!(encoder.take_output().unwrap(), b"li1ee");
+
+        encoder.emit_token(Token::List).unwrap();
+        encoder.emit_token(Token::Dict).unwrap();
+        encoder.reset();
+        encoder.emit_int(1).unwrap();
+        assert_eq!(encoder.take_output().unwrap(), b"i1e");
+    }
+
+    #[test]
+    fn take_output_into_should_recycle_the_buffers() {
+        let mut encoder = Encoder::new();
+        let mut message = Vec::with_capacity(64);
+        let recycled = message.as_ptr();
+
+        encoder.emit_str("first").unwrap();
+        let written = encoder.output.as_ptr();
+        encoder.take_output_into(&mut message).unwrap();
+        assert_eq!(message, b"5:first");
+        assert_eq!(message.as_ptr(), written);
+        assert_eq!(encoder.output.as_ptr(), recycled);
+        assert!(encoder.output.is_empty());
+
+        encoder.emit_int(2).unwrap();
+        encoder.take_output_into(&mut message).unwrap();
+        assert_eq!(message, b"i2e");
+        assert_eq!(message.as_ptr(), recycled);
+        assert_eq!(encoder.output.as_ptr(), written);
+
+        encoder.emit_token(Token::List).unwrap();
+        let error = Error::from(StructureError::UnexpectedEof);
+        assert_eq!(encoder.take_output_into(&mut message), Err(error));
+        assert_eq!(message, b"i2e");
+    }
 }
@@ -1,33 +1,56 @@
+use core::fmt::{self, Display};
 #[cfg(feature = "std")]
 use std::sync::Arc;
 
-use snafu::Snafu;
-
 use crate::state_tracker;
 
 /// An enumeration of potential errors that appear during bencode encoding.
-#[derive(Debug, Clone, Snafu)]
+#[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum Error {
     /// Error that occurs if the serialized structure contains invalid semantics.
     #[cfg(feature = "std")]
-    #[snafu(display("malformed content discovered: {}", source))]
     MalformedContent {
         source: Arc<dyn std::error::Error + Send + Sync>,
     },
 
     /// Error that occurs if the serialized structure contains invalid semantics.
     #[cfg(not(feature = "std"))]
-    #[snafu(display("malformed content discovered"))]
     MalformedContent,
 
     /// Error in the bencode structure (e.g. a missing field end separator).
-    #[snafu(display("bencode encoding corrupted"))]
     StructureError {
         source: state_tracker::StructureError,
     },
 }
 
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Error::MalformedContent { source } => {
+                write!(f, "malformed content discovered: {}", source)
+            },
+            #[cfg(not(feature = "std"))]
+            Error::MalformedContent => write!(f, "malformed content discovered"),
+            Error::StructureError { .. } => write!(f, "bencode encoding corrupted"),
+        }
+    }
+}
+
+// Not derived, so that the cause of `MalformedContent` is exposed as is and not behind its `Arc`.
+impl snafu::Error for Error {
+    fn source(&self) -> Option<&(dyn snafu::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Error::MalformedContent { source } => Some(&**source),
+            #[cfg(not(feature = "std"))]
+            Error::MalformedContent => None,
+            Error::StructureError { source } => Some(source),
+        }
+    }
+}
+
 impl Error {
     /// Raised when there is a general error while deserializing a type.
     /// The message should not be capitalized and should not end with a period.
@@ -49,6 +72,22 @@ impl Error {
     }
 }
 
+/// Errors wrapped in `MalformedContent` are compared by their message.
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            #[cfg(feature = "std")]
+            (Error::MalformedContent { source: a }, Error::MalformedContent { source: b }) => {
+                Arc::ptr_eq(a, b) || a.to_string() == b.to_string()
+            },
+            #[cfg(not(feature = "std"))]
+            (Error::MalformedContent, Error::MalformedContent) => true,
+            (Error::StructureError { source: a }, Error::StructureError { source: b }) => a == b,
+            _ => false,
+        }
+    }
+}
+
 impl From<state_tracker::StructureError> for Error {
     fn from(error: state_tracker::StructureError) -> Self {
         Error::StructureError { source: error }
@@ -63,3 +102,38 @@ fn encoding_errors_are_sync_send() {
     is_send::<Error>();
     is_sync::<Error>();
 }
+
+#[test]
+fn encoding_errors_should_be_comparable() {
+    use state_tracker::StructureError;
+
+    assert_eq!(
+        Error::from(StructureError::UnsortedKeys),
+        Error::from(StructureError::UnsortedKeys)
+    );
+    assert_ne!(
+        Error::from(StructureError::UnsortedKeys),
+        Error::from(StructureError::UnexpectedEof)
+    );
+    assert_ne!(
+        Error::from(StructureError::UnsortedKeys),
+        Error::malformed_content("x".parse::<u8>().unwrap_err())
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn encoding_errors_should_chain_their_cause() {
+    use std::error::Error as _;
+
+    let cause = "x".parse::<u8>().unwrap_err();
+    let error = Error::malformed_content(cause.clone());
+    let source = error.source().expect("wrapped cause");
+    assert_eq!(source.downcast_ref(), Some(&cause));
+
+    let error = Error::from(state_tracker::StructureError::UnsortedKeys);
+    let source = error.source().expect("structure error");
+    assert!(source
+        .downcast_ref::<state_tracker::StructureError>()
+        .is_some());
+}
@@ -1,10 +1,90 @@
 #[cfg(not(feature = "std"))]
-use core::fmt::Display;
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use core::fmt::{Display, Write};
 #[cfg(feature = "std")]
-use std::fmt::Display;
+use std::fmt::{Display, Write};
 
 /// A value that can be formatted as a decimal integer
-pub trait PrintableInteger: Display {}
+pub trait PrintableInteger: Display {
+    /// Append the decimal representation of the value to `output`.
+    ///
+    /// The default implementation uses the `Display` implementation; the implementations for the
+    /// primitive integer types use a faster formatter.
+    #[doc(hidden)]
+    fn write_decimal(&self, output: &mut Vec<u8>) {
+        write!(OutputWriter(output), "{}", self).expect("Writing to a Vec can't fail");
+    }
+}
+
+/// Formats values directly into the output buffer, without a temporary `String`
+struct OutputWriter<'a>(&'a mut Vec<u8>);
+
+impl Write for OutputWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// The decimal digits of all numbers from 00 to 99, so that two digits can be formatted per
+/// division
+const DIGIT_PAIRS: &[u8; 200] = b"\
+    0001020304050607080910111213141516171819\
+    2021222324252627282930313233343536373839\
+    4041424344454647484950515253545556575859\
+    6061626364656667686970717273747576777879\
+    8081828384858687888990919293949596979899";
+
+fn write_u64(mut value: u64, output: &mut Vec<u8>) {
+    // u64::max_value() has 20 digits
+    let mut buffer = [0u8; 20];
+    let mut start = buffer.len();
+
+    while value >= 100 {
+        let pair = (value % 100) as usize * 2;
+        value /= 100;
+        start -= 2;
+        buffer[start..start + 2].copy_from_slice(&DIGIT_PAIRS[pair..pair + 2]);
+    }
+    if value >= 10 {
+        let pair = value as usize * 2;
+        start -= 2;
+        buffer[start..start + 2].copy_from_slice(&DIGIT_PAIRS[pair..pair + 2]);
+    } else {
+        start -= 1;
+        buffer[start] = b'0' + value as u8;
+    }
+
+    output.extend_from_slice(&buffer[start..]);
+}
+
+macro_rules! impl_unsigned {
+    ($($type:ty)*) => {$(
+        impl PrintableInteger for $type {
+            fn write_decimal(&self, output: &mut Vec<u8>) {
+                write_u64(*self as u64, output);
+            }
+        }
+    )*}
+}
+
+macro_rules! impl_signed {
+    ($($type:ty)*) => {$(
+        impl PrintableInteger for $type {
+            fn write_decimal(&self, output: &mut Vec<u8>) {
+                let value = *self as i64;
+                if value < 0 {
+                    output.push(b'-');
+                    // Wrapping also covers i64::min_value(), whose magnitude doesn't fit into an i64
+                    write_u64(value.wrapping_neg() as u64, output);
+                } else {
+                    write_u64(value as u64, output);
+                }
+            }
+        }
+    )*}
+}
 
 macro_rules! impl_integer {
     ($($type:ty)*) => {$(
@@ -12,4 +92,60 @@ macro_rules! impl_integer {
     )*}
 }
 
-impl_integer!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+// 128-bit divisions are slow enough that the table doesn't pay off
+impl_unsigned!(u8 u16 u32 u64 usize);
+impl_signed!(i8 i16 i32 i64 isize);
+impl_integer!(u128 i128);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+
+    fn formatted<T: PrintableInteger>(value: T) -> Vec<u8> {
+        let mut output = Vec::new();
+        value.write_decimal(&mut output);
+        output
+    }
+
+    #[test]
+    fn integers_should_format_like_display() {
+        let values = [
+            0,
+            1,
+            9,
+            10,
+            99,
+            100,
+            101,
+            12345,
+            -1,
+            -10,
+            -100,
+            i64::max_value(),
+        ];
+        for &value in &values {
+            assert_eq!(formatted(value), value.to_string().as_bytes());
+        }
+        for shift in 0..64 {
+            let value = 1u64 << shift;
+            assert_eq!(formatted(value), value.to_string().as_bytes());
+            assert_eq!(formatted(value - 1), (value - 1).to_string().as_bytes());
+        }
+        assert_eq!(
+            formatted(u64::max_value()),
+            u64::max_value().to_string().as_bytes()
+        );
+        assert_eq!(
+            formatted(i64::min_value()),
+            i64::min_value().to_string().as_bytes()
+        );
+        assert_eq!(formatted(i8::min_value()), b"-128");
+        assert_eq!(
+            formatted(u128::max_value()),
+            u128::max_value().to_string().as_bytes()
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use alloc::{string::String, vec::Vec};
+use core::fmt::Debug;
+
+use crate::{
+    decoding::{decode_exact, FromBencode},
+    encoding::ToBencode,
+    value::Value,
+};
+
+/// Assert that `value` encodes to canonical bencode, panicking otherwise.
+///
+/// The encoding is decoded again with the strict decoder, which rejects non-canonical input such
+/// as unsorted or duplicate keys and integers with leading zeros, and the decoded value has to
+/// encode to exactly the same bytes. This is meant for testing [`ToBencode`] implementations,
+/// e.g. ones that emit integers through a custom
+/// [`PrintableInteger`](crate::encoding::PrintableInteger).
+///
+/// ```
+/// # use bendy::encoding::assert_canonical;
+/// #
+/// assert_canonical(&vec![1, 2, 3]);
+/// ```
+#[rustversion::attr(since(1.46), track_caller)]
+pub fn assert_canonical<T: ToBencode>(value: &T) {
+    let encoded = encode(value);
+
+    let decoded = match decode_exact::<Value>(&encoded) {
+        Ok(decoded) => decoded,
+        Err(error) => panic!(
+            "The value encoded to non-canonical bencode: {}",
+            error.display_with_source(&encoded)
+        ),
+    };
+
+    let reencoded = decoded
+        .to_bencode()
+        .expect("A decoded value should always be encodable");
+    assert!(
+        reencoded == encoded,
+        "The value encoded to `{}`, but its canonical encoding is `{}`",
+        String::from_utf8_lossy(&encoded),
+        String::from_utf8_lossy(&reencoded)
+    );
+}
+
+/// Assert that `value` survives a round trip through bencode, panicking otherwise.
+///
+/// The value is encoded and decoded again, which must yield an equal value and consume the whole
+/// encoding. The decoded value then has to encode to exactly the same bytes, so that encoding is
+/// idempotent. This is meant for testing types that implement both [`ToBencode`] and
+/// [`FromBencode`].
+///
+/// ```
+/// # use bendy::encoding::roundtrip_ok;
+/// #
+/// roundtrip_ok(vec![String::from("foo"), String::from("bar")]);
+/// roundtrip_ok(-42i64);
+/// ```
+#[rustversion::attr(since(1.46), track_caller)]
+pub fn roundtrip_ok<T>(value: T)
+where
+    T: ToBencode + FromBencode + PartialEq + Debug,
+{
+    let encoded = encode(&value);
+
+    let decoded = match decode_exact::<T>(&encoded) {
+        Ok(decoded) => decoded,
+        Err(error) => panic!(
+            "Failed to decode the encoded value: {}",
+            error.display_with_source(&encoded)
+        ),
+    };
+    assert!(
+        decoded == value,
+        "The value {:?} encoded to `{}`, which decoded to {:?}",
+        value,
+        String::from_utf8_lossy(&encoded),
+        decoded
+    );
+
+    let reencoded = encode(&decoded);
+    assert!(
+        reencoded == encoded,
+        "The value encoded to `{}`, but the decoded value encoded to `{}`",
+        String::from_utf8_lossy(&encoded),
+        String::from_utf8_lossy(&reencoded)
+    );
+}
+
+#[rustversion::attr(since(1.46), track_caller)]
+fn encode<T: ToBencode>(value: &T) -> Vec<u8> {
+    match value.to_bencode() {
+        Ok(encoded) => encoded,
+        Err(error) => panic!("Failed to encode the value: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use core::fmt;
+
+    use crate::{
+        decoding::{self, Object},
+        encoding::{Error, PrintableInteger, SingleItemEncoder},
+    };
+
+    struct Padded(u8);
+
+    impl fmt::Display for Padded {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:02}", self.0)
+        }
+    }
+
+    impl PrintableInteger for Padded {}
+
+    struct PaddedList;
+
+    impl ToBencode for PaddedList {
+        const MAX_DEPTH: usize = 1;
+
+        fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+            encoder.emit_list(|e| {
+                e.emit_int(Padded(10))?;
+                e.emit_int(Padded(1))
+            })
+        }
+    }
+
+    #[test]
+    fn canonical_values_should_pass() {
+        assert_canonical(&1);
+        assert_canonical(&"foo");
+        assert_canonical(&Value::List(Default::default()));
+    }
+
+    #[test]
+    #[should_panic(expected = "non-canonical bencode")]
+    fn padded_integers_should_be_caught() {
+        assert_canonical(&PaddedList);
+    }
+
+    /// Forgets all but the lowest bit when decoded
+    #[derive(Debug, PartialEq)]
+    struct Lossy(u8);
+
+    impl ToBencode for Lossy {
+        const MAX_DEPTH: usize = 0;
+
+        fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+            encoder.emit_int(self.0)
+        }
+    }
+
+    impl FromBencode for Lossy {
+        fn decode_bencode_object(object: Object) -> Result<Self, decoding::Error> {
+            u8::decode_bencode_object(object).map(|value| Lossy(value & 1))
+        }
+    }
+
+    #[test]
+    fn lossless_values_should_round_trip() {
+        roundtrip_ok(Lossy(1));
+        roundtrip_ok(Value::List(Default::default()));
+        roundtrip_ok(String::from("foo"));
+    }
+
+    #[test]
+    #[should_panic(expected = "which decoded to Lossy(0)")]
+    fn lossy_values_should_be_caught() {
+        roundtrip_ok(Lossy(2));
+    }
+}
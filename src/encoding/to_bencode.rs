@@ -1,6 +1,8 @@
 #[cfg(not(feature = "std"))]
 use alloc::{
-    collections::{BTreeMap, LinkedList, VecDeque},
+    borrow::Cow,
+    boxed::Box,
+    collections::{BTreeMap, BinaryHeap, LinkedList, VecDeque},
     rc::Rc,
     string::String,
     sync::Arc,
@@ -9,10 +11,13 @@ use alloc::{
 
 #[cfg(feature = "std")]
 use std::{
-    collections::{BTreeMap, HashMap, LinkedList, VecDeque},
+    borrow::Cow,
+    collections::{BTreeMap, BinaryHeap, HashMap, LinkedList, VecDeque},
+    ffi::{CStr, CString},
     hash::{BuildHasher, Hash},
     rc::Rc,
     sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use crate::encoding::{Encoder, Error, SingleItemEncoder};
@@ -28,18 +33,125 @@ pub trait ToBencode {
 
     /// Encode this object to a byte string
     fn to_bencode(&self) -> Result<Vec<u8>, Error> {
-        let mut encoder = Encoder::new().with_max_depth(Self::MAX_DEPTH);
-        encoder.emit_with(|e| self.encode(e).map_err(Error::into))?;
+        self.to_bencode_with_max_depth(Self::MAX_DEPTH)
+    }
+
+    /// Encode this object to a byte string, allowing it to nest up to `max_depth` levels instead
+    /// of [`ToBencode::MAX_DEPTH`]. This is meant for types without a static depth bound, such
+    /// as recursive types, which declare a `MAX_DEPTH` of 0.
+    fn to_bencode_with_max_depth(&self, max_depth: usize) -> Result<Vec<u8>, Error> {
+        let mut encoder = Encoder::new().with_max_depth(max_depth);
+        encoder.emit_with(|e| self.encode(e))?;
 
         let bytes = encoder.get_output()?;
         Ok(bytes)
     }
+
+    /// Encode a slice of objects of this type as a list
+    ///
+    /// This is what the implementations for slices and `Vec` use, so that types with a cheaper
+    /// way to encode many values at once can provide it. The integer types use this to format
+    /// the whole list in one go instead of emitting the items one by one.
+    fn encode_slice(items: &[Self], encoder: SingleItemEncoder) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        encoder.emit_list(|e| {
+            for item in items {
+                e.emit(item)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Encode a slice of objects of this type held in a `Cow<[Self]>`
+    ///
+    /// This defaults to [`ToBencode::encode_slice`]. Only `u8` overrides it, so that a
+    /// `Cow<[u8]>` is encoded as a byte string like a `Cow<str>`, while a `Vec<u8>` remains a
+    /// list of integers.
+    #[doc(hidden)]
+    fn encode_cow_slice(items: &[Self], encoder: SingleItemEncoder) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        Self::encode_slice(items, encoder)
+    }
+}
+
+/// An object-safe companion of [`ToBencode`], which allows encoding values of different types
+/// through trait objects, e.g. as elements of a `Vec<Box<dyn DynToBencode>>`.
+///
+/// It is implemented for every `ToBencode` type, so it never needs to be implemented by hand.
+///
+/// ```
+/// # use bendy::encoding::{DynToBencode, ToBencode};
+/// #
+/// let items: Vec<Box<dyn DynToBencode>> = vec![Box::new(1), Box::new("two")];
+/// assert_eq!(items.to_bencode().unwrap(), b"li1e3:twoe");
+/// ```
+pub trait DynToBencode {
+    /// The maximum depth that this object could encode to, see [`ToBencode::MAX_DEPTH`]
+    fn max_depth(&self) -> usize;
+
+    /// Encode this object into the bencode stream, see [`ToBencode::encode`]
+    fn dyn_encode(&self, encoder: SingleItemEncoder) -> Result<(), Error>;
+}
+
+impl<T: ToBencode> DynToBencode for T {
+    fn max_depth(&self) -> usize {
+        T::MAX_DEPTH
+    }
+
+    fn dyn_encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        self.encode(encoder)
+    }
 }
 
 /// Wrapper to allow `Vec<u8>` encoding as bencode string element.
 #[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq, PartialOrd, Ord)]
 pub struct AsString<I>(pub I);
 
+/// Wrapper to decode an integer leniently, clamping values that don't fit into `T` to its
+/// bounds.
+///
+/// Decoding a plain integer type such as `u8` fails if the bencoded integer is out of its range.
+/// Decoding a `Saturating<u8>` instead yields `Saturating(255)` for any integer above 255 and
+/// `Saturating(0)` for any negative integer. Integers in range are decoded as usual, and
+/// malformed integers are still rejected. Encoding is the same as for `T` itself.
+///
+/// ```
+/// # use bendy::{decoding::FromBencode, encoding::Saturating};
+/// #
+/// assert_eq!(Saturating::<u8>::from_bencode(b"i300e").unwrap(), Saturating(255));
+/// assert_eq!(Saturating::<u8>::from_bencode(b"i-1e").unwrap(), Saturating(0));
+/// assert_eq!(Saturating::<i8>::from_bencode(b"i-42e").unwrap(), Saturating(-42));
+/// assert!(u8::from_bencode(b"i300e").is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Saturating<T>(pub T);
+
+/// Wrapper to encode a [`Duration`] or [`SystemTime`] as an integer number of whole seconds.
+///
+/// A `SystemTime` is encoded relative to the Unix epoch, so times before the epoch are encoded as
+/// negative integers.
+///
+/// [`Duration`]: std::time::Duration
+/// [`SystemTime`]: std::time::SystemTime
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Seconds<T>(pub T);
+
+/// Wrapper to encode a [`Duration`] or [`SystemTime`] as an integer number of whole milliseconds.
+///
+/// A `SystemTime` is encoded relative to the Unix epoch, so times before the epoch are encoded as
+/// negative integers.
+///
+/// [`Duration`]: std::time::Duration
+/// [`SystemTime`]: std::time::SystemTime
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Millis<T>(pub T);
+
 // Forwarding impls
 impl<'a, E: 'a + ToBencode + Sized> ToBencode for &'a E {
     const MAX_DEPTH: usize = E::MAX_DEPTH;
@@ -49,12 +161,68 @@ impl<'a, E: 'a + ToBencode + Sized> ToBencode for &'a E {
     }
 }
 
-#[cfg(feature = "std")]
+impl<'a, E: 'a + ToBencode + Sized> ToBencode for &'a mut E {
+    const MAX_DEPTH: usize = E::MAX_DEPTH;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        E::encode(self, encoder)
+    }
+}
+
 impl<E: ToBencode> ToBencode for Box<E> {
     const MAX_DEPTH: usize = E::MAX_DEPTH;
 
     fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
-        E::encode(&*self, encoder)
+        E::encode(self, encoder)
+    }
+}
+
+/// As the depth of a trait object is only known at runtime, nesting it in other containers
+/// effectively lifts their depth limit. Encoding a trait object by itself with
+/// [`ToBencode::to_bencode`] uses its actual depth, which is available as
+/// `boxed.as_ref().max_depth()`.
+impl ToBencode for Box<dyn DynToBencode> {
+    // This leaves some room for external containers.
+    const MAX_DEPTH: usize = usize::max_value() / 4;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        self.as_ref().dyn_encode(encoder)
+    }
+
+    fn to_bencode(&self) -> Result<Vec<u8>, Error> {
+        self.to_bencode_with_max_depth(self.as_ref().max_depth())
+    }
+}
+
+impl<'a> ToBencode for Cow<'a, str> {
+    const MAX_DEPTH: usize = 0;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_str(self)
+    }
+}
+
+/// Encodes like a slice of `T`, except that a `Cow<[u8]>` is encoded as a byte string like a
+/// `Cow<str>`.
+impl<'a, T> ToBencode for Cow<'a, [T]>
+where
+    T: ToBencode + Clone,
+{
+    const MAX_DEPTH: usize = T::MAX_DEPTH + 1;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        T::encode_cow_slice(self, encoder)
+    }
+}
+
+impl<'a, T> ToBencode for Cow<'a, T>
+where
+    T: ToBencode + Clone,
+{
+    const MAX_DEPTH: usize = T::MAX_DEPTH;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        (**self).encode(encoder)
     }
 }
 
@@ -62,7 +230,7 @@ impl<E: ToBencode> ToBencode for Rc<E> {
     const MAX_DEPTH: usize = E::MAX_DEPTH;
 
     fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
-        E::encode(&*self, encoder)
+        E::encode(self, encoder)
     }
 }
 
@@ -70,16 +238,16 @@ impl<E: ToBencode> ToBencode for Arc<E> {
     const MAX_DEPTH: usize = E::MAX_DEPTH;
 
     fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
-        E::encode(&*self, encoder)
+        E::encode(self, encoder)
     }
 }
 
 // Base type impls
-impl<'a> ToBencode for &'a str {
+impl ToBencode for &str {
     const MAX_DEPTH: usize = 0;
 
     fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
-        encoder.emit_str(self).map_err(Error::from)
+        encoder.emit_str(self)
     }
 }
 
@@ -87,7 +255,46 @@ impl ToBencode for String {
     const MAX_DEPTH: usize = 0;
 
     fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
-        encoder.emit_str(self).map_err(Error::from)
+        encoder.emit_str(self)
+    }
+}
+
+/// C strings are encoded as byte strings without their nul terminator.
+#[cfg(feature = "std")]
+impl ToBencode for &CStr {
+    const MAX_DEPTH: usize = 0;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_bytes(self.to_bytes())
+    }
+}
+
+/// C strings are encoded as byte strings without their nul terminator.
+#[cfg(feature = "std")]
+impl ToBencode for CString {
+    const MAX_DEPTH: usize = 0;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_bytes(self.as_bytes())
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl ToBencode for bytes_::Bytes {
+    const MAX_DEPTH: usize = 0;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_bytes(self)
+    }
+}
+
+/// Encodes the 16 bytes of the UUID as a byte string.
+#[cfg(feature = "uuid")]
+impl ToBencode for uuid_::Uuid {
+    const MAX_DEPTH: usize = 0;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_bytes(self.as_bytes())
     }
 }
 
@@ -97,13 +304,33 @@ macro_rules! impl_encodable_integer {
             const MAX_DEPTH: usize = 1;
 
             fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
-                encoder.emit_int(*self).map_err(Error::from)
+                encoder.emit_int(*self)
+            }
+
+            fn encode_slice(items: &[Self], encoder: SingleItemEncoder) -> Result<(), Error> {
+                encoder.emit_int_list(items)
             }
         }
     )*}
 }
 
-impl_encodable_integer!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+impl_encodable_integer!(u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+impl ToBencode for u8 {
+    const MAX_DEPTH: usize = 1;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_int(*self)
+    }
+
+    fn encode_slice(items: &[Self], encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_int_list(items)
+    }
+
+    fn encode_cow_slice(items: &[Self], encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_bytes(items)
+    }
+}
 
 macro_rules! impl_encodable_iterable {
     ($($type:ident)*) => {$(
@@ -127,26 +354,61 @@ macro_rules! impl_encodable_iterable {
     )*}
 }
 
-impl_encodable_iterable!(Vec VecDeque LinkedList);
+impl_encodable_iterable!(VecDeque LinkedList);
 
-impl<'a, ContentT> ToBencode for &'a [ContentT]
+impl<ContentT: ToBencode> ToBencode for Vec<ContentT> {
+    const MAX_DEPTH: usize = ContentT::MAX_DEPTH + 1;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        ContentT::encode_slice(self, encoder)
+    }
+}
+
+/// As the iteration order of a heap is unspecified, the elements are written in ascending order,
+/// like [`BinaryHeap::into_sorted_vec`] returns them, so that equal heaps encode the same.
+impl<ContentT> ToBencode for BinaryHeap<ContentT>
+where
+    ContentT: ToBencode + Ord,
+{
+    const MAX_DEPTH: usize = ContentT::MAX_DEPTH + 1;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        let mut items: Vec<&ContentT> = self.iter().collect();
+        items.sort();
+        items.as_slice().encode(encoder)
+    }
+}
+
+impl<ContentT> ToBencode for &[ContentT]
 where
     ContentT: ToBencode,
 {
     const MAX_DEPTH: usize = ContentT::MAX_DEPTH + 1;
 
     fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
-        encoder.emit_list(|e| {
-            for item in *self {
-                e.emit(item)?;
-            }
-            Ok(())
-        })?;
+        ContentT::encode_slice(self, encoder)
+    }
+}
 
-        Ok(())
+impl<ContentT: ToBencode> ToBencode for Box<[ContentT]> {
+    const MAX_DEPTH: usize = ContentT::MAX_DEPTH + 1;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        (&**self).encode(encoder)
+    }
+}
+
+impl<ContentT: ToBencode> ToBencode for Arc<[ContentT]> {
+    const MAX_DEPTH: usize = ContentT::MAX_DEPTH + 1;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        (&**self).encode(encoder)
     }
 }
 
+/// The entries are written in the order of the map without sorting them again, which is the
+/// byte order required by bencode for keys like `Vec<u8>`, `String` or `&str`. Keys whose `Ord`
+/// disagrees with the order of their bytes are reported as unsorted.
 impl<K: AsRef<[u8]>, V: ToBencode> ToBencode for BTreeMap<K, V> {
     const MAX_DEPTH: usize = V::MAX_DEPTH + 1;
 
@@ -200,6 +462,59 @@ where
     }
 }
 
+impl<T: ToBencode> ToBencode for Saturating<T> {
+    const MAX_DEPTH: usize = T::MAX_DEPTH;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        self.0.encode(encoder)
+    }
+}
+
+#[cfg(feature = "std")]
+fn since_epoch(time: SystemTime, unit: impl Fn(Duration) -> u128) -> i128 {
+    // Any duration representable by `Duration` fits into an i128, even in milliseconds
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => unit(after) as i128,
+        Err(before) => -(unit(before.duration()) as i128),
+    }
+}
+
+#[cfg(feature = "std")]
+impl ToBencode for Seconds<Duration> {
+    const MAX_DEPTH: usize = 0;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_int(self.0.as_secs())
+    }
+}
+
+#[cfg(feature = "std")]
+impl ToBencode for Millis<Duration> {
+    const MAX_DEPTH: usize = 0;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_int(self.0.as_millis())
+    }
+}
+
+#[cfg(feature = "std")]
+impl ToBencode for Seconds<SystemTime> {
+    const MAX_DEPTH: usize = 0;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_int(since_epoch(self.0, |d| u128::from(d.as_secs())))
+    }
+}
+
+#[cfg(feature = "std")]
+impl ToBencode for Millis<SystemTime> {
+    const MAX_DEPTH: usize = 0;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
+        encoder.emit_int(since_epoch(self.0, |d| d.as_millis()))
+    }
+}
+
 impl<I> AsRef<[u8]> for AsString<I>
 where
     I: AsRef<[u8]>,
@@ -222,9 +537,10 @@ where
 mod test {
 
     #[cfg(not(feature = "std"))]
-    use alloc::{borrow::ToOwned, vec};
+    use alloc::{borrow::ToOwned, format, vec};
 
     use super::*;
+    use crate::state_tracker::StructureError;
 
     struct Foo {
         bar: u32,
@@ -237,7 +553,7 @@ mod test {
 
         fn encode(&self, encoder: SingleItemEncoder) -> Result<(), Error> {
             encoder.emit_dict(|mut e| {
-                e.emit_pair(b"bar", &self.bar)?;
+                e.emit_pair(b"bar", self.bar)?;
                 e.emit_pair(b"baz", &self.baz)?;
                 e.emit_pair(b"qux", AsString(&self.qux))?;
                 Ok(())
@@ -247,6 +563,114 @@ mod test {
         }
     }
 
+    #[test]
+    fn btree_maps_should_encode_in_map_order() {
+        let map: BTreeMap<String, u32> = (0..10_000).map(|i| (format!("{:05}", i), i)).collect();
+        let encoded = map.to_bencode().unwrap();
+        assert!(encoded.starts_with(b"d5:00000i0e5:00001i1e"));
+        assert!(encoded.ends_with(b"5:09999i9999ee"));
+
+        // A key order that differs from the byte order shows that the map isn't sorted again
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Reversed(core::cmp::Reverse<&'static str>);
+
+        impl AsRef<[u8]> for Reversed {
+            fn as_ref(&self) -> &[u8] {
+                (self.0).0.as_bytes()
+            }
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert(Reversed(core::cmp::Reverse("a")), 1);
+        map.insert(Reversed(core::cmp::Reverse("b")), 2);
+        assert!(map.to_bencode().is_err());
+    }
+
+    #[test]
+    fn mutable_references_should_encode_like_their_referent() {
+        fn encode(value: impl ToBencode) -> Vec<u8> {
+            value.to_bencode().unwrap()
+        }
+
+        let mut value = vec![1, 2];
+        assert_eq!(encode(&mut value), b"li1ei2ee");
+        assert_eq!(<&mut Vec<i32>>::MAX_DEPTH, Vec::<i32>::MAX_DEPTH);
+    }
+
+    #[test]
+    fn cows_should_encode_like_their_borrowed_type() {
+        let text: Cow<str> = Cow::Borrowed("foo");
+        assert_eq!(text.to_bencode().unwrap(), b"3:foo");
+        let text: Cow<str> = Cow::Owned("foo".to_owned());
+        assert_eq!(text.to_bencode().unwrap(), b"3:foo");
+
+        let list: Cow<[i64]> = Cow::Owned(vec![1, 2]);
+        assert_eq!(list.to_bencode().unwrap(), b"li1ei2ee");
+        assert_eq!(<Cow<[i64]>>::MAX_DEPTH, 2);
+
+        let nested: Cow<[Vec<i64>]> = Cow::Owned(vec![vec![1]]);
+        assert_eq!(nested.to_bencode().unwrap(), b"lli1eee");
+
+        let bytes: Cow<[u8]> = Cow::Borrowed(b"ab");
+        assert_eq!(bytes.to_bencode().unwrap(), b"2:ab");
+        let bytes: Cow<[u8]> = Cow::Owned(b"ab".to_vec());
+        assert_eq!(bytes.to_bencode().unwrap(), b"2:ab");
+        assert_eq!(vec![b'a', b'b'].to_bencode().unwrap(), b"li97ei98ee");
+
+        let number: Cow<i64> = Cow::Owned(1);
+        assert_eq!(number.to_bencode().unwrap(), b"i1e");
+    }
+
+    #[test]
+    fn binary_heaps_should_encode_in_ascending_order() {
+        let heap: BinaryHeap<i64> = vec![2, 3, 1, 2].into_iter().collect();
+        assert_eq!(heap.to_bencode().unwrap(), b"li1ei2ei2ei3ee");
+        assert_eq!(BinaryHeap::<i64>::new().to_bencode().unwrap(), b"le");
+    }
+
+    #[test]
+    fn trait_objects_should_encode_with_their_own_depth() {
+        let nested: Box<dyn DynToBencode> = Box::new(vec![vec![1]]);
+        assert_eq!(nested.as_ref().max_depth(), Vec::<Vec<i32>>::MAX_DEPTH);
+        assert_eq!(nested.to_bencode().unwrap(), b"lli1eee");
+
+        let items: Vec<Box<dyn DynToBencode>> = vec![nested, Box::new(AsString(b"ab"))];
+        assert_eq!(items.to_bencode().unwrap(), b"llli1eee2:abe");
+    }
+
+    #[test]
+    fn integer_lists_should_encode_like_other_lists() {
+        let values: Vec<i64> = vec![0, -1, 12, -345, 6789];
+        let items: VecDeque<i64> = values.iter().copied().collect();
+
+        assert_eq!(values.to_bencode().unwrap(), items.to_bencode().unwrap());
+        assert_eq!(
+            values.as_slice().to_bencode().unwrap(),
+            b"li0ei-1ei12ei-345ei6789ee"
+        );
+        assert_eq!(Vec::<u8>::new().to_bencode().unwrap(), b"le");
+        assert_eq!(vec![vec![1u8], vec![]].to_bencode().unwrap(), b"lli1eelee");
+    }
+
+    #[test]
+    fn integer_lists_should_respect_the_state_of_the_encoder() {
+        let error = vec![1u32].to_bencode_with_max_depth(0).unwrap_err();
+        let expected = StructureError::NestingTooDeep {
+            depth: 1,
+            max_depth: 0,
+        };
+        assert_eq!(error, Error::from(expected));
+
+        let mut encoder = Encoder::new();
+        encoder
+            .emit_dict(|mut e| {
+                e.emit_pair(b"a", vec![1u32, 2])?;
+                e.emit_pair(b"b", &[3u64][..])
+            })
+            .unwrap();
+        assert_eq!(encoder.get_output().unwrap(), b"d1:ali1ei2ee1:bli3eee");
+    }
+
     #[test]
     fn simple_encodable_works() {
         let mut encoder = Encoder::new();
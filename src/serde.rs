@@ -12,8 +12,8 @@
 //! assert_eq!(to_bytes(&10).unwrap(), b"i10e");
 //! assert_eq!(from_bytes::<u64>(b"i10e").unwrap(), 10);
 //!
-//! #[serde(crate = "serde_")]
 //! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! #[serde(crate = "serde_")]
 //! struct Foo {
 //!     bar: bool,
 //! }
@@ -40,7 +40,8 @@
 //! - maps, including BTreeMap and HashMap: bencoded dictionaries.
 //! - record structs: Represented as bencoded dictionaries with the fields of the
 //!   struct represented as UTF-8 keys mapped to the bencoded serializations of the
-//!   values.
+//!   values. Keys are the field names after applying `#[serde(rename)]` and
+//!   `#[serde(rename_all)]`, and are sorted after renaming.
 //! - tuple structs: Represented as bencoded lists containing the serialized values
 //!   of the fields.
 //! - unit structs: Represented as the empty bencode list, `le`.
@@ -52,6 +53,23 @@
 //! - enum struct variants: Represented as a dict mapping the name of the variant
 //!   to the struct representation of the fields of the variant.
 //! - untagged enums: Repesented as the variant value without any surrounding dictionary.
+//! - internally tagged enums (`#[serde(tag = "...")]`): Represented as the dict of the
+//!   variant's fields with the tag key mapped to the name of the variant, sorted among the
+//!   other keys.
+//! - adjacently tagged enums (`#[serde(tag = "...", content = "...")]`): Represented as a dict
+//!   mapping the tag key to the name of the variant and the content key to the value the
+//!   variant contains.
+//!
+//! Untagged, internally tagged and adjacently tagged enums are deserialized by buffering the
+//! input without knowing the target types. As the representations of `bool`, `char`, `f32`,
+//! `f64`, `()` and `Option` can only be told apart with those types at hand, fields of these
+//! types can't be deserialized inside such enums. Integers, strings, byte strings, sequences,
+//! maps and structs work as expected.
+//!
+//! Integers deserialized without a known type, e.g. into `serde_json::Value` or inside
+//! untagged enums, are passed to the visitor as the first of `i64`, `u64`, `i128` and `u128` that
+//! can hold them. Integers outside of the `i128` and `u128` ranges can't be deserialized that way,
+//! as that would need a bignum type.
 //!
 //! Bencode dictionary keys may only be byte strings. For this reason, map types with
 //! keys that do not serialize as byte strings are unsupported.
@@ -97,23 +115,23 @@
 //! map.insert("bar", 2);
 //! repr(map, "d3:bari2e3:fooi1ee");
 //!
-//! #[serde(crate = "serde_")]
 //! #[derive(Serialize)]
+//! #[serde(crate = "serde_")]
 //! struct Unit;
 //! repr(Unit, "le");
 //!
-//! #[serde(crate = "serde_")]
 //! #[derive(Serialize)]
+//! #[serde(crate = "serde_")]
 //! struct Newtype(String);
 //! repr(Newtype("foo".into()), "3:foo");
 //!
-//! #[serde(crate = "serde_")]
 //! #[derive(Serialize)]
+//! #[serde(crate = "serde_")]
 //! struct Tuple(bool, i32);
 //! repr(Tuple(false, 100), "li0ei100ee");
 //!
-//! #[serde(crate = "serde_")]
 //! #[derive(Serialize)]
+//! #[serde(crate = "serde_")]
 //! struct Record {
 //!     a: String,
 //!     b: bool,
@@ -127,8 +145,8 @@
 //!     "d1:a5:hello1:bi0ee",
 //! );
 //!
-//! #[serde(crate = "serde_")]
 //! #[derive(Serialize)]
+//! #[serde(crate = "serde_")]
 //! enum Enum {
 //!     Unit,
 //!     Newtype(i32),
@@ -141,9 +159,9 @@
 //! repr(Enum::Tuple(true, 10), "d5:Tupleli1ei10eee");
 //! repr(Enum::Struct { a: 'x', b: true }, "d6:Structd1:a1:x1:bi1eee");
 //!
+//! #[derive(Serialize)]
 //! #[serde(untagged)]
 //! #[serde(crate = "serde_")]
-//! #[derive(Serialize)]
 //! enum Untagged {
 //!     Foo { x: i32 },
 //!     Bar { y: char },
@@ -151,6 +169,24 @@
 //!
 //! repr(Untagged::Foo { x: -1 }, "d1:xi-1ee");
 //! repr(Untagged::Bar { y: 'z' }, "d1:y1:ze");
+//!
+//! #[derive(Serialize)]
+//! #[serde(tag = "type")]
+//! #[serde(crate = "serde_")]
+//! enum Internal {
+//!     Foo { x: i32 },
+//! }
+//!
+//! repr(Internal::Foo { x: -1 }, "d4:type3:Foo1:xi-1ee");
+//!
+//! #[derive(Serialize)]
+//! #[serde(tag = "t", content = "c")]
+//! #[serde(crate = "serde_")]
+//! enum Adjacent {
+//!     Foo(i32),
+//! }
+//!
+//! repr(Adjacent::Foo(-1), "d1:ci-1e1:t3:Fooe");
 //! ```
 
 mod common;
@@ -190,7 +226,7 @@ mod tests {
                     have,
                     want,
                     "Expected `{}` but got `{}` when serializing `{:?}`",
-                    String::from_utf8_lossy(&want),
+                    String::from_utf8_lossy(want),
                     String::from_utf8_lossy(&have),
                     value
                 );
@@ -229,7 +265,7 @@ mod tests {
                     have,
                     want,
                     "Expected `{}` but got `{}` when serializing `{:?}`",
-                    String::from_utf8_lossy(&want),
+                    String::from_utf8_lossy(want),
                     String::from_utf8_lossy(&have),
                     value
                 );
@@ -470,6 +506,199 @@ mod tests {
         case(Untagged::Bar { y: "z".into() }, "d1:y1:ze");
     }
 
+    #[test]
+    fn internally_tagged_enum() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(tag = "type")]
+        #[serde(crate = "serde_")]
+        enum Internal {
+            Unit,
+            Newtype(Record),
+            Struct { a: i32, z: String },
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(crate = "serde_")]
+        struct Record {
+            x: Vec<u32>,
+        }
+
+        case(Internal::Unit, "d4:type4:Unite");
+        case(
+            Internal::Newtype(Record { x: vec![1] }),
+            "d4:type7:Newtype1:xli1eee",
+        );
+        case(
+            Internal::Struct {
+                a: 1,
+                z: "z".into(),
+            },
+            "d1:ai1e4:type6:Struct1:z1:ze",
+        );
+    }
+
+    #[test]
+    fn adjacently_tagged_enum() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(tag = "t", content = "c")]
+        #[serde(crate = "serde_")]
+        enum Adjacent {
+            Unit,
+            Newtype(i32),
+            Tuple(i32, String),
+            Struct { a: i32 },
+        }
+
+        case(Adjacent::Unit, "d1:t4:Unite");
+        case(Adjacent::Newtype(-1), "d1:ci-1e1:t7:Newtypee");
+        case(Adjacent::Tuple(1, "a".into()), "d1:cli1e1:ae1:t5:Tuplee");
+        case(Adjacent::Struct { a: 1 }, "d1:cd1:ai1ee1:t6:Structe");
+    }
+
+    #[test]
+    fn tagged_enum_with_bool_field() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(tag = "type")]
+        #[serde(crate = "serde_")]
+        enum Internal {
+            Struct { b: bool },
+        }
+
+        // The buffered integer can't be told apart from a bool, see the module docs
+        let encoded = to_bytes(&Internal::Struct { b: true }).unwrap();
+        assert_eq!(encoded, b"d1:bi1e4:type6:Structe");
+        assert!(from_bytes::<Internal>(&encoded).is_err());
+    }
+
+    #[test]
+    fn untyped_integers_should_use_smallest_fitting_type() {
+        use serde::de::{Deserialize, Visitor};
+        use std::fmt;
+
+        #[derive(Debug, PartialEq)]
+        enum Integer {
+            I64(i64),
+            U64(u64),
+            I128(i128),
+            U128(u128),
+        }
+
+        struct IntegerVisitor;
+
+        impl<'de> Visitor<'de> for IntegerVisitor {
+            type Value = Integer;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an integer")
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Integer, E> {
+                Ok(Integer::I64(value))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Integer, E> {
+                Ok(Integer::U64(value))
+            }
+
+            fn visit_i128<E>(self, value: i128) -> Result<Integer, E> {
+                Ok(Integer::I128(value))
+            }
+
+            fn visit_u128<E>(self, value: u128) -> Result<Integer, E> {
+                Ok(Integer::U128(value))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for Integer {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(IntegerVisitor)
+            }
+        }
+
+        let any = |input: &[u8]| from_bytes::<Integer>(input).unwrap();
+        assert_eq!(any(b"i-1e"), Integer::I64(-1));
+        assert_eq!(
+            any(b"i18446744073709551615e"),
+            Integer::U64(u64::max_value())
+        );
+        assert_eq!(
+            any(b"i-9223372036854775809e"),
+            Integer::I128(i128::from(i64::min_value()) - 1)
+        );
+        assert_eq!(
+            any(b"i340282366920938463463374607431768211455e"),
+            Integer::U128(u128::max_value())
+        );
+        assert!(from_bytes::<Integer>(b"i340282366920938463463374607431768211456e").is_err());
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(untagged)]
+        #[serde(crate = "serde_")]
+        enum Counter {
+            Big(u64),
+        }
+
+        assert_eq!(
+            from_bytes::<Counter>(b"i18446744073709551615e").unwrap(),
+            Counter::Big(u64::max_value())
+        );
+    }
+
+    #[test]
+    fn renamed_fields_should_be_sorted_by_their_new_names() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(crate = "serde_")]
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        struct Screaming {
+            zebra: i32,
+            #[serde(rename = "aardvark")]
+            yak: i32,
+            x_ray: i32,
+        }
+
+        // Uppercase keys sort before lowercase ones, the reverse of the declaration order
+        case(
+            Screaming {
+                zebra: 1,
+                yak: 2,
+                x_ray: 3,
+            },
+            "d5:X_RAYi3e5:ZEBRAi1e8:aardvarki2ee",
+        );
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(crate = "serde_")]
+        #[serde(rename_all = "camelCase")]
+        struct Camel {
+            b_field: i32,
+            a: i32,
+            #[serde(rename = "Z")]
+            c: i32,
+        }
+
+        case(
+            Camel {
+                b_field: 1,
+                a: 2,
+                c: 3,
+            },
+            "d1:Zi3e1:ai2e6:bFieldi1ee",
+        );
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(crate = "serde_")]
+        #[serde(rename_all = "kebab-case")]
+        enum Kebab {
+            #[serde(rename_all = "UPPERCASE")]
+            StructVariant { b: i32, a_b: i32 },
+        }
+
+        case(
+            Kebab::StructVariant { b: 1, a_b: 2 },
+            "d14:struct-variantd3:A_Bi2e1:Bi1eee",
+        );
+    }
+
     #[test]
     fn flatten() {
         #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -521,6 +750,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn empty_input_should_be_reported_as_such() {
+        match from_bytes::<u32>(b"") {
+            Err(Error::Decode(error)) => assert!(error.is_empty_input()),
+            other => panic!("Unexpected result {:?}", other),
+        }
+        match from_bytes::<u32>(b"i1") {
+            Err(Error::Decode(error)) => assert!(!error.is_empty_input()),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn decoding_errors_should_report_their_offset() {
+        #[derive(Deserialize, Debug)]
+        #[serde(crate = "serde_")]
+        struct Foo {
+            #[allow(dead_code)]
+            name: String,
+        }
+
+        let offset = |result: Result<Foo>| result.unwrap_err().offset();
+
+        assert_eq!(offset(from_bytes(b"d4:namei1ee")), Some(7));
+        assert_eq!(offset(from_bytes(b"d4:name2:\xff\xffe")), Some(7));
+        assert_eq!(offset(from_bytes(b"d4:name3:fooi01ee")), Some(14));
+        assert_eq!(offset(from_bytes(b"d4:name3:foo")), Some(12));
+        assert_eq!(offset(from_bytes(b"de")), None);
+    }
+
     #[test]
     fn trailing_bytes_allow() {
         assert_matches!(
@@ -11,9 +11,26 @@ where
 }
 
 /// Bencode deserializer
+///
+/// Decoding errors are reported at the offset of the token that caused them, see
+/// [`Error::offset`].
 pub struct Deserializer<'de> {
     forbid_trailing_bytes: bool,
-    tokens: Peekable<Tokens<'de>>,
+    tokens: Peekable<PositionedTokens<'de>>,
+    /// The offset of the token that was read last
+    offset: usize,
+}
+
+/// The tokens of the input, along with the offsets they start at
+struct PositionedTokens<'de>(Tokens<'de>);
+
+impl<'de> Iterator for PositionedTokens<'de> {
+    type Item = (usize, Result<Token<'de>, decoding::Error>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.0.offset();
+        self.0.next().map(|token| (offset, token))
+    }
 }
 
 impl<'de> Deserializer<'de> {
@@ -21,7 +38,8 @@ impl<'de> Deserializer<'de> {
     pub fn from_bytes(input: &'de [u8]) -> Self {
         Deserializer {
             forbid_trailing_bytes: false,
-            tokens: Decoder::new(input).tokens().peekable(),
+            tokens: PositionedTokens(Decoder::new(input).tokens()).peekable(),
+            offset: 0,
         }
     }
 
@@ -36,7 +54,11 @@ impl<'de> Deserializer<'de> {
     where
         T: Deserialize<'de>,
     {
-        let t = T::deserialize(&mut self)?;
+        if self.tokens.peek().is_none() {
+            return Err(Error::Decode(decoding::ErrorKind::EmptyInput.into()));
+        }
+
+        let t = T::deserialize(&mut self).map_err(|error| error.at_offset(self.offset))?;
 
         if self.forbid_trailing_bytes && self.tokens.next().is_some() {
             return Err(Error::TrailingBytes);
@@ -49,7 +71,10 @@ impl<'de> Deserializer<'de> {
 impl<'de> Deserializer<'de> {
     fn next_token(&mut self) -> Result<Token<'de>> {
         match self.tokens.next() {
-            Some(result) => Ok(result?),
+            Some((offset, result)) => {
+                self.offset = offset;
+                Ok(result?)
+            },
             None => Err(Error::Decode(StructureError::UnexpectedEof.into())),
         }
     }
@@ -61,6 +86,25 @@ impl<'de> Deserializer<'de> {
         }
     }
 
+    /// Visit an integer as the first of `i64`, `u64`, `i128` and `u128` that can hold it, so
+    /// that schema-less consumers can deal with values outside of the `i64` range.
+    fn deserialize_any_integer<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let text = self.next_integer()?;
+
+        if let Ok(value) = text.parse::<i64>() {
+            visitor.visit_i64(value)
+        } else if let Ok(value) = text.parse::<u64>() {
+            visitor.visit_u64(value)
+        } else if let Ok(value) = text.parse::<i128>() {
+            visitor.visit_i128(value)
+        } else {
+            visitor.visit_u128(text.parse()?)
+        }
+    }
+
     fn next_bytes(&mut self) -> Result<&'de [u8]> {
         match self.next_token()? {
             Token::String(bytes) => Ok(bytes),
@@ -106,7 +150,7 @@ impl<'de> Deserializer<'de> {
     }
 
     fn peek(&mut self) -> Option<Token<'de>> {
-        if let Some(Ok(token)) = self.tokens.peek() {
+        if let Some((_, Ok(token))) = self.tokens.peek() {
             Some(*token)
         } else {
             None
@@ -114,7 +158,7 @@ impl<'de> Deserializer<'de> {
     }
 }
 
-impl<'de, 'a> serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
+impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
@@ -125,7 +169,7 @@ impl<'de, 'a> serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
             Some(Token::Dict) => self.deserialize_map(visitor),
             Some(Token::String(_)) => self.deserialize_bytes(visitor),
             Some(Token::List) => self.deserialize_seq(visitor),
-            Some(Token::Num(_)) => self.deserialize_i64(visitor),
+            Some(Token::Num(_)) => self.deserialize_any_integer(visitor),
             Some(Token::End) => Err(Error::Decode(StructureError::invalid_state("End").into())),
             None => Err(Error::Decode(StructureError::UnexpectedEof.into())),
         }
@@ -1,11 +1,11 @@
-///! Serde error and result types
+//! Serde error and result types
 use crate::serde::common::*;
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 /// An enumeration of potential errors that appear during serde serialiation and
 /// deserialization
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Error {
     /// Error that occurs if a map with a key type which does not serialize to
     /// a byte string is encountered
@@ -37,6 +37,37 @@ pub enum Error {
     Decode(decoding::Error),
 }
 
+impl Error {
+    /// The byte offset into the input at which deserialization failed, if known.
+    ///
+    /// Failures in the bencode structure and mismatches between the input and the expected
+    /// type are reported at the offset of the offending token. Errors raised by `Deserialize`
+    /// implementations through [`serde::de::Error::custom`] and the other variants don't carry
+    /// an offset.
+    ///
+    /// ```
+    /// # use bendy::serde::from_bytes;
+    /// #
+    /// let error = from_bytes::<Vec<u32>>(b"li1e3:fooe").unwrap_err();
+    ///
+    /// assert_eq!(error.offset(), Some(4));
+    /// ```
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::Decode(error) => error.offset(),
+            _ => None,
+        }
+    }
+
+    /// Attach an offset to a decoding error, unless it already carries one.
+    pub(crate) fn at_offset(self, offset: usize) -> Self {
+        match self {
+            Error::Decode(error) => Error::Decode(error.at_offset(offset)),
+            other => other,
+        }
+    }
+}
+
 impl From<encoding::Error> for Error {
     fn from(encoding_error: encoding::Error) -> Self {
         Error::Encode(encoding_error)
@@ -47,12 +47,12 @@ impl Serializer {
         Ok(())
     }
 
-    fn begin_struct(&mut self) -> Result<StructSerializer> {
+    fn begin_struct(&mut self) -> Result<StructSerializer<'_>> {
         let encoder = self.encoder.begin_unsorted_dict()?;
         Ok(StructSerializer::new(&mut self.encoder, encoder))
     }
 
-    fn begin_map(&mut self) -> Result<MapSerializer> {
+    fn begin_map(&mut self) -> Result<MapSerializer<'_>> {
         let encoder = self.encoder.begin_unsorted_dict()?;
         Ok(MapSerializer::new(&mut self.encoder, encoder))
     }
@@ -256,7 +256,7 @@ impl<'a> serde::ser::Serializer for &'a mut Serializer {
     }
 }
 
-impl<'a> SerializeSeq for &'a mut Serializer {
+impl SerializeSeq for &mut Serializer {
     type Error = Error;
     type Ok = ();
 
@@ -273,7 +273,7 @@ impl<'a> SerializeSeq for &'a mut Serializer {
     }
 }
 
-impl<'a> SerializeTuple for &'a mut Serializer {
+impl SerializeTuple for &mut Serializer {
     type Error = Error;
     type Ok = ();
 
@@ -290,7 +290,7 @@ impl<'a> SerializeTuple for &'a mut Serializer {
     }
 }
 
-impl<'a> SerializeTupleStruct for &'a mut Serializer {
+impl SerializeTupleStruct for &mut Serializer {
     type Error = Error;
     type Ok = ();
 
@@ -307,7 +307,7 @@ impl<'a> SerializeTupleStruct for &'a mut Serializer {
     }
 }
 
-impl<'a> SerializeMap for &'a mut Serializer {
+impl SerializeMap for &mut Serializer {
     type Error = Error;
     type Ok = ();
 
@@ -330,7 +330,7 @@ impl<'a> SerializeMap for &'a mut Serializer {
     }
 }
 
-impl<'a> SerializeTupleVariant for &'a mut Serializer {
+impl SerializeTupleVariant for &mut Serializer {
     type Error = Error;
     type Ok = ();
 
@@ -348,7 +348,7 @@ impl<'a> SerializeTupleVariant for &'a mut Serializer {
     }
 }
 
-impl<'a> SerializeStructVariant for &'a mut Serializer {
+impl SerializeStructVariant for &mut Serializer {
     type Error = Error;
     type Ok = ();
 
@@ -49,6 +49,31 @@ where
         self.max_depth - self.state.len()
     }
 
+    pub fn depth(&self) -> usize {
+        self.state.len()
+    }
+
+    /// Forget all open containers and any latched error, keeping the max depth
+    pub fn reset(&mut self) {
+        self.state.clear();
+    }
+
+    /// The error for opening a container beyond the configured max depth
+    fn nesting_too_deep(&self) -> StructureError {
+        StructureError::NestingTooDeep {
+            depth: self.state.len() + 1,
+            max_depth: self.max_depth,
+        }
+    }
+
+    /// Whether the next token is read as the key of a dict
+    pub fn expects_key(&self) -> bool {
+        match self.state.last() {
+            Some(State::MapKey(_)) => true,
+            _ => false,
+        }
+    }
+
     /// Observe that an EOF was seen. This function is idempotent.
     pub fn observe_eof(&mut self) -> Result<(), E> {
         self.check_error()?;
@@ -93,9 +118,9 @@ where
             (Some(MapValue(label)), List) | (Some(MapValue(label)), Dict) => {
                 let dummy: &[u8] = &[];
                 *self.state.last_mut().unwrap() =
-                    MapKey(Some(std::mem::replace(label, dummy.into())));
+                    MapKey(Some(core::mem::replace(label, dummy.into())));
                 if self.state.len() >= self.max_depth {
-                    return self.latch_err(Err(E::from(StructureError::NestingTooDeep)));
+                    return self.latch_err(Err(E::from(self.nesting_too_deep())));
                 }
                 self.state
                     .push(if token == &List { Seq } else { MapKey(None) });
@@ -109,11 +134,11 @@ where
             (Some(MapValue(label)), _) => {
                 let dummy: &[u8] = &[];
                 *self.state.last_mut().unwrap() =
-                    MapKey(Some(std::mem::replace(label, dummy.into())));
+                    MapKey(Some(core::mem::replace(label, dummy.into())));
             },
             (_oldstate, List) | (_oldstate, Dict) => {
                 if self.state.len() >= self.max_depth {
-                    return self.latch_err(Err(E::from(StructureError::NestingTooDeep)));
+                    return self.latch_err(Err(E::from(self.nesting_too_deep())));
                 }
                 self.state
                     .push(if token == &List { Seq } else { MapKey(None) });
@@ -131,8 +156,17 @@ where
         result
     }
 
+    /// Replace the latched error, if any, with the result of applying `op` to it
+    pub fn map_latched_err(&mut self, op: impl FnOnce(E) -> E) {
+        match self.state.pop() {
+            Some(State::Failed(error)) => self.state.push(State::Failed(op(error))),
+            Some(state) => self.state.push(state),
+            None => {},
+        }
+    }
+
     pub fn check_error(&self) -> Result<(), E> {
-        if let Some(&State::Failed(ref error)) = self.state.peek() {
+        if let Some(State::Failed(error)) = self.state.peek() {
             Err(error.clone())
         } else {
             Ok(())
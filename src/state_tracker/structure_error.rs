@@ -29,9 +29,62 @@ pub enum StructureError {
     #[snafu(display("Malformed number of unexpected character: {}", unexpected))]
     SyntaxError { unexpected: String },
 
+    /// A number broke one of the rules for canonical integers: no leading zeros, no negative
+    /// zero, no lone minus sign and at least one digit.
+    #[snafu(display("Illegal {} at offset {}: {}", number, offset, rule))]
+    IllegalInteger {
+        number: &'static str,
+        rule: &'static str,
+        offset: usize,
+    },
+
+    /// A byte string exceeded the configured maximum length.
+    #[snafu(display(
+        "Byte string of length {} exceeds the maximum of {}",
+        length,
+        max_length
+    ))]
+    StringTooLong { length: usize, max_length: usize },
+
+    /// A byte string declared a length that no input can satisfy, as its end would lie beyond
+    /// the largest possible buffer. Strings that are merely cut off are reported as
+    /// `UnexpectedEof` instead.
+    #[snafu(display(
+        "Declared byte string length {} exceeds the {} remaining bytes of input",
+        length,
+        remaining
+    ))]
+    StringLengthExceedsInput { length: usize, remaining: usize },
+
+    /// An object extended past the input length declared to a bounded decoder.
+    #[snafu(display(
+        "Object extends past the declared input length of {} bytes",
+        declared_length
+    ))]
+    ExceedsDeclaredLength { declared_length: usize },
+
+    /// The byte strings of the input exceeded the configured maximum total length.
+    #[snafu(display(
+        "Byte strings of total length {} exceed the maximum of {}",
+        total_length,
+        max_total_length
+    ))]
+    StringsTooLong {
+        total_length: usize,
+        max_total_length: usize,
+    },
+
+    /// A dict key was rejected by the configured key validator.
+    #[snafu(display("Dict key {:?} was rejected", key))]
+    InvalidKey { key: String },
+
+    /// Data remained after the end of a message that should span the whole input.
+    #[snafu(display("Unexpected trailing data after the end of the message"))]
+    TrailingData,
+
     /// Exceeded the recursion limit.
-    #[snafu(display("Maximum nesting depth exceeded"))]
-    NestingTooDeep,
+    #[snafu(display("Maximum nesting depth of {} exceeded at depth {}", max_depth, depth))]
+    NestingTooDeep { depth: usize, max_depth: usize },
 }
 
 impl StructureError {
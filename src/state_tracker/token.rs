@@ -1,4 +1,17 @@
+use core::{ascii, fmt};
+
 /// A raw bencode token
+///
+/// The `Display` implementation shows a token in bencode notation meant for logging token
+/// streams: non-printable bytes are escaped, and byte strings longer than
+/// [`Token::PREVIEW_LENGTH`] bytes are truncated.
+///
+/// ```
+/// # use bendy::state_tracker::Token;
+/// #
+/// assert_eq!(Token::String(b"caf\xc3\xa9").to_string(), r"5:caf\xc3\xa9");
+/// assert_eq!(Token::String(&[b'a'; 40]).to_string(), format!("40:{}...", "a".repeat(32)));
+/// ```
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
 pub enum Token<'a> {
     /// The beginning of a list
@@ -14,6 +27,9 @@ pub enum Token<'a> {
 }
 
 impl<'a> Token<'a> {
+    /// The number of bytes of a byte string shown by the `Display` implementation
+    pub const PREVIEW_LENGTH: usize = 32;
+
     pub fn name(&self) -> &'static str {
         match *self {
             Token::Dict => "Dict",
@@ -24,3 +40,59 @@ impl<'a> Token<'a> {
         }
     }
 }
+
+impl<'a> fmt::Display for Token<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Token::Dict => f.write_str("d"),
+            Token::End => f.write_str("e"),
+            Token::List => f.write_str("l"),
+            Token::Num(num) => write!(f, "i{}e", num),
+            Token::String(bytes) => {
+                write!(f, "{}:", bytes.len())?;
+                for &byte in bytes.iter().take(Self::PREVIEW_LENGTH) {
+                    for escaped in ascii::escape_default(byte) {
+                        write!(f, "{}", escaped as char)?;
+                    }
+                }
+                if bytes.len() > Self::PREVIEW_LENGTH {
+                    f.write_str("...")?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(not(feature = "std"))]
+    use alloc::{format, string::ToString, vec, vec::Vec};
+
+    use super::*;
+
+    #[test]
+    fn tokens_should_display_as_bencode() {
+        let tokens = [
+            Token::Dict,
+            Token::String(b"foo"),
+            Token::List,
+            Token::Num("-12"),
+            Token::End,
+            Token::End,
+        ];
+        let displayed: Vec<_> = tokens.iter().map(ToString::to_string).collect();
+
+        assert_eq!(displayed, ["d", "3:foo", "l", "i-12e", "e", "e"]);
+    }
+
+    #[test]
+    fn long_strings_should_be_truncated_and_escaped() {
+        let mut bytes = vec![b'\n'; 2];
+        bytes.extend_from_slice(&[b'x'; 40]);
+        let expected = format!("42:\\n\\n{}...", "x".repeat(30));
+
+        assert_eq!(Token::String(&bytes).to_string(), expected);
+        assert_eq!(Token::String(b"").to_string(), "0:");
+    }
+}
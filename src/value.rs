@@ -2,13 +2,17 @@
 //! they can be cloned and traversed multiple times.
 //!
 //! `Value` implements `FromBencode`, `ToBencode`. If the `serde` feature is
-//! enabled, it also implements `Serialize` and `Deserialize`.
+//! enabled, it also implements `Serialize` and `Deserialize`. If the `json`
+//! feature is enabled, it can be converted from and to `serde_json::Value`.
 
 use alloc::{
     borrow::{Cow, ToOwned},
-    collections::BTreeMap,
+    collections::{btree_map, BTreeMap},
+    format,
+    string::{String, ToString},
     vec::Vec,
 };
+use core::slice;
 
 #[cfg(feature = "serde")]
 use std::{
@@ -26,13 +30,20 @@ use serde::{
     Serialize,
 };
 
+use snafu::Snafu;
+
 use crate::{
-    decoding::{FromBencode, Object},
-    encoding::{SingleItemEncoder, ToBencode},
+    decoding::{self, Decoder, FromBencode, Object},
+    encoding::{self, SingleItemEncoder, ToBencode},
+    state_tracker::StructureError,
 };
 
 /// An owned or borrowed bencoded value.
-#[derive(PartialEq, Eq, Clone, Debug)]
+///
+/// Equal values have the same canonical encoding, and `Hash` is consistent with that: dicts
+/// are hashed in sorted key order and it makes no difference whether data is borrowed or owned.
+/// This allows values to be used as keys of a `HashMap` or members of a `HashSet`.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum Value<'a> {
     /// An owned or borrowed byte string
     Bytes(Cow<'a, [u8]>),
@@ -44,8 +55,532 @@ pub enum Value<'a> {
     List(Vec<Value<'a>>),
 }
 
+/// A step of a path into a nested [`Value`], used to describe where in a tree a value is.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum PathSegment<'a> {
+    /// The value of a dict entry with the given key
+    Key(&'a [u8]),
+    /// The element of a list at the given index
+    Index(usize),
+}
+
+/// An edit to apply to a [`Value`] with [`Value::apply_patch`].
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum PatchOp<'a> {
+    /// Replace the value at the path. Dict entries are created if they don't exist yet, but list
+    /// elements have to exist.
+    Set(Value<'a>),
+    /// Remove the dict entry or list element at the path.
+    Remove,
+    /// Add a new dict entry, which must not exist yet, or insert a list element before the given
+    /// index, shifting later elements back. An index equal to the length of the list appends.
+    Insert(Value<'a>),
+}
+
+/// The reason an entry of a patch couldn't be applied, see [`Value::apply_patch`].
+///
+/// Each variant carries the index of the failed entry within the patch and a description of
+/// the path involved, as produced by [`Value::describe_path`].
+#[derive(PartialEq, Eq, Clone, Debug, Snafu)]
+pub enum PatchError {
+    /// The path doesn't lead to an existing value.
+    #[snafu(display("patch entry {}: no value at {}", entry, path))]
+    NotFound { entry: usize, path: String },
+
+    /// An insertion targeted a dict key that already exists.
+    #[snafu(display("patch entry {}: {} already exists", entry, path))]
+    AlreadyExists { entry: usize, path: String },
+
+    /// A key was applied to something other than a dict, or an index to something other than
+    /// a list.
+    #[snafu(display("patch entry {}: the value at {} is not a {}", entry, path, expected))]
+    WrongContainer {
+        entry: usize,
+        path: String,
+        expected: &'static str,
+    },
+
+    /// The root value can only be replaced, not removed or inserted.
+    #[snafu(display("patch entry {}: the root value can only be set", entry))]
+    InvalidRootOperation { entry: usize },
+}
+
+/// The reason a flat map couldn't be turned back into a tree, see [`Value::unflatten`].
+#[derive(PartialEq, Eq, Clone, Debug, Snafu)]
+pub enum UnflattenError {
+    /// The path isn't in the syntax produced by [`Value::flatten`].
+    #[snafu(display("invalid path {:?}", path))]
+    InvalidPath { path: String },
+
+    /// The path leads into a value that is set by another path, or into a container of the
+    /// wrong kind.
+    #[snafu(display("the path {:?} conflicts with another path", path))]
+    Conflict { path: String },
+
+    /// A list has no element at this path, although it has elements at higher indices.
+    #[snafu(display("no value at {:?}, although the list has later elements", path))]
+    MissingElement { path: String },
+}
+
+/// A segment of a path parsed by [`Value::unflatten`]
+enum FlatSegment {
+    Key(Vec<u8>),
+    Index(usize),
+}
+
+/// A tree under construction by [`Value::unflatten`]
+enum FlatNode<'a> {
+    Leaf(Value<'a>),
+    Dict(BTreeMap<Vec<u8>, FlatNode<'a>>),
+    List(BTreeMap<usize, FlatNode<'a>>),
+}
+
+impl<'a> FlatNode<'a> {
+    /// A node holding only `value` at `path`
+    fn new(path: &[FlatSegment], value: Value<'a>) -> Self {
+        let (first, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return FlatNode::Leaf(value),
+        };
+
+        let child = FlatNode::new(rest, value);
+        match first {
+            FlatSegment::Key(key) => {
+                FlatNode::Dict(Some((key.clone(), child)).into_iter().collect())
+            },
+            FlatSegment::Index(index) => {
+                FlatNode::List(Some((*index, child)).into_iter().collect())
+            },
+        }
+    }
+
+    /// Add `value` at `path` below this node, returning false if that conflicts with the values
+    /// that are already there.
+    fn insert(&mut self, path: &[FlatSegment], value: Value<'a>) -> bool {
+        let (first, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return false,
+        };
+
+        match (self, first) {
+            (FlatNode::Dict(dict), FlatSegment::Key(key)) => match dict.entry(key.clone()) {
+                btree_map::Entry::Vacant(entry) => {
+                    entry.insert(FlatNode::new(rest, value));
+                    true
+                },
+                btree_map::Entry::Occupied(entry) => entry.into_mut().insert(rest, value),
+            },
+            (FlatNode::List(list), FlatSegment::Index(index)) => match list.entry(*index) {
+                btree_map::Entry::Vacant(entry) => {
+                    entry.insert(FlatNode::new(rest, value));
+                    true
+                },
+                btree_map::Entry::Occupied(entry) => entry.into_mut().insert(rest, value),
+            },
+            _ => false,
+        }
+    }
+
+    /// Turn the node at `path` into a value, checking that lists have no gaps
+    fn into_value(self, path: &mut String) -> Result<Value<'a>, UnflattenError> {
+        let parent_length = path.len();
+        match self {
+            FlatNode::Leaf(value) => Ok(value),
+            FlatNode::Dict(dict) => {
+                let mut result = BTreeMap::new();
+                for (key, node) in dict {
+                    push_path_key(&key, path);
+                    result.insert(Cow::Owned(key), node.into_value(path)?);
+                    path.truncate(parent_length);
+                }
+                Ok(Value::Dict(result))
+            },
+            FlatNode::List(list) => {
+                let mut result = Vec::with_capacity(list.len());
+                for (index, node) in list {
+                    path.push_str(&format!("[{}]", result.len()));
+                    if index != result.len() {
+                        return Err(UnflattenError::MissingElement { path: path.clone() });
+                    }
+                    result.push(node.into_value(path)?);
+                    path.truncate(parent_length);
+                }
+                Ok(Value::List(result))
+            },
+        }
+    }
+}
+
+/// Append `key` to a flattened path: after a `.`, unless it is the first key of the path and not
+/// empty, and with `%`, `.`, `[` and all bytes outside of printable ASCII escaped as `%XX`
+fn push_path_key(key: &[u8], path: &mut String) {
+    if !path.is_empty() || key.is_empty() {
+        path.push('.');
+    }
+    for &byte in key {
+        match byte {
+            b'%' | b'.' | b'[' => path.push_str(&format!("%{:02X}", byte)),
+            b' '..=b'~' => path.push(byte as char),
+            _ => path.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+}
+
+/// Split a flattened path into its segments, or return `None` if it is malformed
+fn parse_flat_path(path: &str) -> Option<Vec<FlatSegment>> {
+    // Split off an escaped key, which ends at the next separator
+    fn split_key(bytes: &[u8]) -> (&[u8], &[u8]) {
+        let end = bytes
+            .iter()
+            .position(|&byte| byte == b'.' || byte == b'[')
+            .unwrap_or(bytes.len());
+        bytes.split_at(end)
+    }
+
+    let mut segments = Vec::new();
+    let mut rest = path.as_bytes();
+
+    // The first key has no leading dot, unless it is empty
+    match rest.first() {
+        None | Some(b'[') | Some(b'.') => {},
+        Some(_) => {
+            let (key, tail) = split_key(rest);
+            segments.push(FlatSegment::Key(unescape_path_key(key)?));
+            rest = tail;
+        },
+    }
+
+    while let Some((&first, tail)) = rest.split_first() {
+        match first {
+            b'.' => {
+                let (key, tail) = split_key(tail);
+                // Only accept the canonical form of each path, like `flatten` writes it
+                if segments.is_empty() && !key.is_empty() {
+                    return None;
+                }
+                segments.push(FlatSegment::Key(unescape_path_key(key)?));
+                rest = tail;
+            },
+            b'[' => {
+                let end = tail.iter().position(|&byte| byte == b']')?;
+                let digits = &tail[..end];
+                if !digits.iter().all(u8::is_ascii_digit) || (digits.starts_with(b"0") && end > 1) {
+                    return None;
+                }
+                let index = core::str::from_utf8(digits).ok()?.parse().ok()?;
+                segments.push(FlatSegment::Index(index));
+                rest = &tail[end + 1..];
+            },
+            _ => return None,
+        }
+    }
+
+    Some(segments)
+}
+
+fn unescape_path_key(escaped: &[u8]) -> Option<Vec<u8>> {
+    let hex_digit = |byte: u8| (byte as char).to_digit(16).map(|digit| digit as u8);
+
+    let mut key = Vec::with_capacity(escaped.len());
+    let mut bytes = escaped.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte == b'%' {
+            let high = hex_digit(*bytes.next()?)?;
+            let low = hex_digit(*bytes.next()?)?;
+            key.push((high << 4) | low);
+        } else {
+            key.push(byte);
+        }
+    }
+
+    Some(key)
+}
+
+impl PatchError {
+    /// The index of the patch entry that failed.
+    pub fn entry(&self) -> usize {
+        match self {
+            PatchError::NotFound { entry, .. }
+            | PatchError::AlreadyExists { entry, .. }
+            | PatchError::WrongContainer { entry, .. }
+            | PatchError::InvalidRootOperation { entry } => *entry,
+        }
+    }
+}
+
 impl<'a> Value<'a> {
+    /// Render a path into a value as a human-readable string, e.g. for error messages.
+    ///
+    /// Keys are prefixed with `/` and indices are written in brackets. Bytes of a key that aren't
+    /// valid UTF-8 are replaced, so the result is meant for display only.
+    ///
+    /// ```
+    /// # use bendy::value::{PathSegment, Value};
+    /// #
+    /// let path = [
+    ///     PathSegment::Key(b"info"),
+    ///     PathSegment::Key(b"files"),
+    ///     PathSegment::Index(2),
+    ///     PathSegment::Key(b"length"),
+    /// ];
+    /// assert_eq!(Value::describe_path(&path), "/info/files[2]/length");
+    /// assert_eq!(Value::describe_path(&[]), "/");
+    /// ```
+    pub fn describe_path(path: &[PathSegment]) -> String {
+        if path.is_empty() {
+            return "/".to_string();
+        }
+
+        let mut description = String::new();
+        for segment in path {
+            match segment {
+                PathSegment::Key(key) => {
+                    description.push('/');
+                    description.push_str(&String::from_utf8_lossy(key));
+                },
+                PathSegment::Index(index) => {
+                    description.push_str(&format!("[{}]", index));
+                },
+            }
+        }
+        description
+    }
+
+    /// Apply a list of edits, in order. Each entry pairs the path of the value to edit with the
+    /// operation to perform there; all segments of a path but the last have to exist.
+    ///
+    /// Application stops at the first entry that fails, whose index is reported in the error.
+    /// Entries before it stay applied, so clone the value first if the edit has to be atomic.
+    ///
+    /// ```
+    /// # use bendy::{decoding::FromBencode, encoding::ToBencode};
+    /// use bendy::value::{PatchOp, PathSegment::*, Value};
+    ///
+    /// let mut value = Value::from_bencode(b"d4:infod6:lengthi3e4:name3:fooee").unwrap();
+    /// value
+    ///     .apply_patch(&[
+    ///         (vec![Key(b"info"), Key(b"length")], PatchOp::Set(Value::Integer(5))),
+    ///         (vec![Key(b"info"), Key(b"name")], PatchOp::Remove),
+    ///         (vec![Key(b"url-list")], PatchOp::Insert(Value::List(vec![]))),
+    ///     ])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(value.to_bencode().unwrap(), b"d4:infod6:lengthi5ee8:url-listlee");
+    ///
+    /// let error = value
+    ///     .apply_patch(&[(vec![Key(b"info"), Key(b"name")], PatchOp::Remove)])
+    ///     .unwrap_err();
+    /// assert_eq!(error.to_string(), "patch entry 0: no value at /info/name");
+    /// ```
+    pub fn apply_patch(
+        &mut self,
+        patch: &[(Vec<PathSegment>, PatchOp<'a>)],
+    ) -> Result<(), PatchError> {
+        for (entry, (path, op)) in patch.iter().enumerate() {
+            self.apply_patch_entry(entry, path, op)?;
+        }
+        Ok(())
+    }
+
+    fn apply_patch_entry(
+        &mut self,
+        entry: usize,
+        path: &[PathSegment],
+        op: &PatchOp<'a>,
+    ) -> Result<(), PatchError> {
+        let (last, parent_path) = match path.split_last() {
+            Some(split) => split,
+            None => match op {
+                PatchOp::Set(value) => {
+                    *self = value.clone();
+                    return Ok(());
+                },
+                _ => return Err(PatchError::InvalidRootOperation { entry }),
+            },
+        };
+
+        let mut parent = self;
+        for (depth, segment) in parent_path.iter().enumerate() {
+            parent = parent
+                .child_mut(entry, &path[..depth], segment)?
+                .ok_or_else(|| PatchError::NotFound {
+                    entry,
+                    path: Value::describe_path(&path[..=depth]),
+                })?;
+        }
+
+        let not_found = || PatchError::NotFound {
+            entry,
+            path: Value::describe_path(path),
+        };
+        match (parent, last) {
+            (Value::Dict(dict), PathSegment::Key(key)) => match op {
+                PatchOp::Set(value) => {
+                    dict.insert(Cow::Owned(key.to_vec()), value.clone());
+                },
+                PatchOp::Remove => {
+                    dict.remove(*key).ok_or_else(not_found)?;
+                },
+                PatchOp::Insert(value) => {
+                    if dict.contains_key(*key) {
+                        return Err(PatchError::AlreadyExists {
+                            entry,
+                            path: Value::describe_path(path),
+                        });
+                    }
+                    dict.insert(Cow::Owned(key.to_vec()), value.clone());
+                },
+            },
+            (Value::List(list), PathSegment::Index(index)) => match op {
+                PatchOp::Set(value) => {
+                    *list.get_mut(*index).ok_or_else(not_found)? = value.clone();
+                },
+                PatchOp::Remove if *index < list.len() => {
+                    list.remove(*index);
+                },
+                PatchOp::Insert(value) if *index <= list.len() => {
+                    list.insert(*index, value.clone());
+                },
+                _ => return Err(not_found()),
+            },
+            (_, segment) => return Err(Self::wrong_container(entry, parent_path, segment)),
+        }
+
+        Ok(())
+    }
+
+    /// The child at `segment`, or an error if this value can't have such a child at all
+    fn child_mut(
+        &mut self,
+        entry: usize,
+        path: &[PathSegment],
+        segment: &PathSegment,
+    ) -> Result<Option<&mut Value<'a>>, PatchError> {
+        match (self, segment) {
+            (Value::Dict(dict), PathSegment::Key(key)) => Ok(dict.get_mut(*key)),
+            (Value::List(list), PathSegment::Index(index)) => Ok(list.get_mut(*index)),
+            (_, segment) => Err(Self::wrong_container(entry, path, segment)),
+        }
+    }
+
+    fn wrong_container(entry: usize, path: &[PathSegment], segment: &PathSegment) -> PatchError {
+        PatchError::WrongContainer {
+            entry,
+            path: Value::describe_path(path),
+            expected: match segment {
+                PathSegment::Key(_) => "dict",
+                PathSegment::Index(_) => "list",
+            },
+        }
+    }
+
+    /// Flatten this value into a map from the path of every leaf to the leaf, e.g. to access a
+    /// document like a flat config store or to diff two documents leaf by leaf.
+    ///
+    /// Leaves are byte strings, integers, and empty lists and dicts. Paths are written like
+    /// `info.files[0].length`:
+    ///
+    /// - dict keys are separated by `.`, and the first key of a path has no leading `.` unless
+    ///   it is empty (so the top-level empty key can be told apart from the root),
+    /// - list indices are written in brackets, like `[0]`,
+    /// - `%`, `.`, `[` and all bytes outside of printable ASCII are escaped in keys as `%XX`,
+    ///   so the key `a.b` is written as `a%2Eb` and the key `%` as `%25`,
+    /// - and the path of the root value is the empty string.
+    ///
+    /// Unlike [`Value::describe_path`], which is meant for display, this syntax is reversible:
+    /// [`Value::unflatten`] turns the map back into the tree.
+    ///
+    /// ```
+    /// # use bendy::{decoding::FromBencode, value::Value};
+    /// #
+    /// let value = Value::from_bencode(b"d3:a.b0:4:infod5:filesld6:lengthi3eeeee").unwrap();
+    /// let flat = value.flatten();
+    ///
+    /// let paths: Vec<_> = flat.keys().map(String::as_str).collect();
+    /// assert_eq!(paths, ["a%2Eb", "info.files[0].length"]);
+    /// assert_eq!(flat["info.files[0].length"], Value::Integer(3));
+    /// assert_eq!(Value::unflatten(flat).unwrap(), value);
+    /// ```
+    pub fn flatten(&self) -> BTreeMap<String, Value<'a>> {
+        let mut entries = BTreeMap::new();
+        self.flatten_into(&mut String::new(), &mut entries);
+        entries
+    }
+
+    fn flatten_into(&self, path: &mut String, entries: &mut BTreeMap<String, Value<'a>>) {
+        let parent_length = path.len();
+        match self {
+            Value::List(list) if !list.is_empty() => {
+                for (index, item) in list.iter().enumerate() {
+                    path.push_str(&format!("[{}]", index));
+                    item.flatten_into(path, entries);
+                    path.truncate(parent_length);
+                }
+            },
+            Value::Dict(dict) if !dict.is_empty() => {
+                for (key, value) in dict {
+                    push_path_key(key, path);
+                    value.flatten_into(path, entries);
+                    path.truncate(parent_length);
+                }
+            },
+            leaf => {
+                entries.insert(path.clone(), leaf.clone());
+            },
+        }
+    }
+
+    /// Rebuild a value from a map of leaf paths as produced by [`Value::flatten`]. An empty map
+    /// yields an empty dict.
+    ///
+    /// This fails if a path is malformed, if paths conflict with each other (e.g. both `a` and
+    /// `a.b`, or `a` and `a[0]`), or if a list would have gaps.
+    ///
+    /// ```
+    /// # use bendy::value::{UnflattenError, Value};
+    /// #
+    /// let mut flat = std::collections::BTreeMap::new();
+    /// flat.insert("name".to_string(), Value::Bytes(b"foo"[..].into()));
+    /// flat.insert("sizes[0]".to_string(), Value::Integer(1));
+    /// flat.insert("sizes[1]".to_string(), Value::Integer(2));
+    ///
+    /// let value = Value::unflatten(flat.clone()).unwrap();
+    /// assert_eq!(value.keys().collect::<Vec<_>>(), [&b"name"[..], b"sizes"]);
+    ///
+    /// flat.insert("sizes[3]".to_string(), Value::Integer(4));
+    /// let error = Value::unflatten(flat).unwrap_err();
+    /// assert_eq!(error, UnflattenError::MissingElement { path: "sizes[2]".into() });
+    /// ```
+    pub fn unflatten(entries: BTreeMap<String, Value<'a>>) -> Result<Value<'a>, UnflattenError> {
+        let mut root: Option<FlatNode<'a>> = None;
+        for (path, value) in entries {
+            let segments = match parse_flat_path(&path) {
+                Some(segments) => segments,
+                None => return Err(UnflattenError::InvalidPath { path }),
+            };
+
+            let inserted = match &mut root {
+                Some(root) => root.insert(&segments, value),
+                None => {
+                    root = Some(FlatNode::new(&segments, value));
+                    true
+                },
+            };
+            if !inserted {
+                return Err(UnflattenError::Conflict { path });
+            }
+        }
+
+        match root {
+            Some(root) => root.into_value(&mut String::new()),
+            None => Ok(Value::Dict(BTreeMap::new())),
+        }
+    }
+
     /// Convert this Value into an owned Value with static lifetime
+    ///
+    /// Borrowed byte strings and keys are copied, so the result no longer refers to the buffer
+    /// the value was decoded from. Data that is already owned is moved rather than copied.
     pub fn into_owned(self) -> Value<'static> {
         match self {
             Value::Bytes(bytes) => Value::Bytes(Cow::Owned(bytes.into_owned())),
@@ -58,6 +593,472 @@ impl<'a> Value<'a> {
             Value::List(list) => Value::List(list.into_iter().map(Value::into_owned).collect()),
         }
     }
+
+    /// Create an owned copy of this Value with static lifetime, without consuming it.
+    ///
+    /// This is the same as `value.clone().into_owned()`, e.g. to keep a value that borrows from
+    /// a transient buffer. Unlike [`Clone::clone`], which keeps borrowed data borrowed, the
+    /// result doesn't depend on the lifetime `'a`.
+    pub fn to_owned_value(&self) -> Value<'static> {
+        self.clone().into_owned()
+    }
+
+    /// The content of an atom: the bytes of a byte string, or the decimal ASCII representation
+    /// of an integer. Returns `None` for lists and dicts.
+    ///
+    /// Note that this is only the payload of the atom, not its bencode encoding, so `3:123` and
+    /// `i123e` have the same content. As integers are stored in parsed form, their content has
+    /// to be allocated.
+    ///
+    /// ```
+    /// # use bendy::value::Value;
+    /// # use std::borrow::Cow;
+    /// #
+    /// let bytes = Value::Bytes(Cow::Borrowed(b"123"));
+    /// assert_eq!(bytes.bytes().as_deref(), Some(&b"123"[..]));
+    /// assert_eq!(Value::Integer(123).bytes().as_deref(), Some(&b"123"[..]));
+    /// assert_eq!(Value::List(vec![]).bytes(), None);
+    /// ```
+    pub fn bytes(&self) -> Option<Cow<'_, [u8]>> {
+        match self {
+            Value::Bytes(bytes) => Some(Cow::Borrowed(bytes)),
+            Value::Integer(integer) => Some(Cow::Owned(integer.to_string().into_bytes())),
+            Value::Dict(_) | Value::List(_) => None,
+        }
+    }
+
+    /// The integers of a list that only contains integers. Returns `None` if this isn't a list
+    /// or if any of its elements isn't an integer.
+    ///
+    /// ```
+    /// # use bendy::{decoding::FromBencode, value::Value};
+    /// #
+    /// let value = Value::from_bencode(b"li1ei2ei3ee").unwrap();
+    /// assert_eq!(value.as_integer_list(), Some(vec![1, 2, 3]));
+    /// assert_eq!(Value::from_bencode(b"li1e1:ae").unwrap().as_integer_list(), None);
+    /// ```
+    pub fn as_integer_list(&self) -> Option<Vec<i64>> {
+        match self {
+            Value::List(list) => list
+                .iter()
+                .map(|item| match item {
+                    Value::Integer(integer) => Some(*integer),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// The byte strings of a list that only contains byte strings. Returns `None` if this isn't
+    /// a list or if any of its elements isn't a byte string.
+    ///
+    /// ```
+    /// # use bendy::{decoding::FromBencode, value::Value};
+    /// #
+    /// let value = Value::from_bencode(b"l1:a2:bce").unwrap();
+    /// assert_eq!(value.as_bytes_list(), Some(vec![&b"a"[..], &b"bc"[..]]));
+    /// assert_eq!(Value::from_bencode(b"l1:ai1ee").unwrap().as_bytes_list(), None);
+    /// ```
+    pub fn as_bytes_list(&self) -> Option<Vec<&[u8]>> {
+        match self {
+            Value::List(list) => list
+                .iter()
+                .map(|item| match item {
+                    Value::Bytes(bytes) => Some(&bytes[..]),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// The entries of a dict in key order, with borrowed keys. Other values have no entries.
+    ///
+    /// ```
+    /// # use bendy::{decoding::FromBencode, value::Value};
+    /// #
+    /// let value = Value::from_bencode(b"d1:ai1e1:bi2ee").unwrap();
+    /// let entries: Vec<_> = value.entries().collect();
+    /// assert_eq!(entries, [(&b"a"[..], &Value::Integer(1)), (&b"b"[..], &Value::Integer(2))]);
+    /// assert_eq!(Value::Integer(1).entries().count(), 0);
+    /// ```
+    pub fn entries(&self) -> impl Iterator<Item = (&[u8], &Value<'a>)> + '_ {
+        let dict = match self {
+            Value::Dict(dict) => Some(dict),
+            _ => None,
+        };
+
+        dict.into_iter()
+            .flat_map(|dict| dict.iter())
+            .map(|(key, value)| (&key[..], value))
+    }
+
+    /// The keys of a dict in order, see [`Value::entries`].
+    pub fn keys(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.entries().map(|(key, _)| key)
+    }
+
+    /// The values of a dict in key order, see [`Value::entries`].
+    pub fn values(&self) -> impl Iterator<Item = &Value<'a>> + '_ {
+        self.entries().map(|(_, value)| value)
+    }
+
+    /// Whether this value, including everything nested in it, would encode canonically, i.e.
+    /// with the keys of every dict unique and sorted by their raw bytes.
+    ///
+    /// Dicts are kept in a `BTreeMap` whose byte string keys compare exactly like bencode
+    /// requires, so every `Value` is canonical by construction, however it was built. This
+    /// check makes that guarantee explicit, e.g. as an assertion before signing an encoding.
+    ///
+    /// ```
+    /// # use bendy::value::{Value, ValueBuilder};
+    /// #
+    /// let value = ValueBuilder::dict()
+    ///     .entry("b", Value::Integer(1))
+    ///     .entry("a", Value::List(vec![]))
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(value.is_canonical());
+    /// ```
+    pub fn is_canonical(&self) -> bool {
+        match self {
+            Value::Bytes(_) | Value::Integer(_) => true,
+            Value::List(list) => list.iter().all(Value::is_canonical),
+            Value::Dict(dict) => {
+                dict.keys()
+                    .zip(dict.keys().skip(1))
+                    .all(|(a, b)| a[..] < b[..])
+                    && dict.values().all(Value::is_canonical)
+            },
+        }
+    }
+
+    /// A new dict holding only the top-level entries of this dict whose keys are listed in
+    /// `keys`, e.g. the fields covered by a signature.
+    ///
+    /// Listed keys missing from the dict are skipped, and the result is canonical like every
+    /// other `Value`. A value that is not a dict has no entries to keep, so the result is an
+    /// empty dict.
+    ///
+    /// ```
+    /// # use bendy::{decoding::FromBencode, encoding::ToBencode, value::Value};
+    /// #
+    /// let value = Value::from_bencode(b"d4:body3:abc2:idi7e3:sig2:xxe").unwrap();
+    /// let signed = value.project(&[&b"id"[..], b"body", b"missing"]);
+    /// assert_eq!(signed.to_bencode().unwrap(), b"d4:body3:abc2:idi7ee");
+    /// ```
+    pub fn project(&self, keys: &[&[u8]]) -> Value<'a> {
+        let projected = match self {
+            Value::Dict(dict) => dict
+                .iter()
+                .filter(|(key, _)| keys.contains(&&key[..]))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            _ => BTreeMap::new(),
+        };
+
+        Value::Dict(projected)
+    }
+
+    /// Reduce this value and everything nested in it to a single result.
+    ///
+    /// The traversal is depth-first and pre-order: `f` sees a list or dict before its contents.
+    /// The entries of a dict are visited in key order, each key (as a `Value::Bytes`) directly
+    /// before its value.
+    ///
+    /// ```
+    /// # use bendy::{decoding::FromBencode, value::Value};
+    /// #
+    /// let value = Value::from_bencode(b"d3:fooli1ei2ee3:quxi3ee").unwrap();
+    /// let sum = value.fold(0, |sum, value| match value {
+    ///     Value::Integer(integer) => sum + integer,
+    ///     _ => sum,
+    /// });
+    /// assert_eq!(sum, 6);
+    /// ```
+    pub fn fold<B, F>(&self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, &Value) -> B,
+    {
+        self.fold_with(init, &mut f)
+    }
+
+    fn fold_with<B, F>(&self, init: B, f: &mut F) -> B
+    where
+        F: FnMut(B, &Value) -> B,
+    {
+        let acc = f(init, self);
+        match self {
+            Value::Bytes(_) | Value::Integer(_) => acc,
+            Value::List(list) => list.iter().fold(acc, |acc, item| item.fold_with(acc, f)),
+            Value::Dict(dict) => dict.iter().fold(acc, |acc, (key, value)| {
+                let acc = f(acc, &Value::Bytes(Cow::Borrowed(key)));
+                value.fold_with(acc, f)
+            }),
+        }
+    }
+
+    /// Decode a value from untrusted input.
+    ///
+    /// Unlike [`FromBencode::from_bencode`], which allows values to be nested almost without
+    /// limit, this rejects input that is nested deeper than `max_depth` or that contains a byte
+    /// string longer than `max_len` bytes.
+    ///
+    /// ```
+    /// # use bendy::value::Value;
+    /// #
+    /// assert!(Value::from_bencode_limited(b"ll3:fooee", 2, 3).is_ok());
+    /// assert!(Value::from_bencode_limited(b"ll3:fooee", 1, 3).is_err());
+    /// assert!(Value::from_bencode_limited(b"ll3:fooee", 2, 2).is_err());
+    /// ```
+    pub fn from_bencode_limited(
+        bytes: &[u8],
+        max_depth: usize,
+        max_len: usize,
+    ) -> Result<Self, decoding::Error> {
+        let mut decoder = Decoder::new(bytes)
+            .with_max_depth(max_depth)
+            .with_max_string_length(max_len);
+        let object = decoder.next_object()?;
+
+        object.map_or(
+            Err(decoding::Error::from(decoding::ErrorKind::EmptyInput)),
+            Self::decode_bencode_object,
+        )
+    }
+
+    /// Encode this value incrementally, in chunks of `chunk_size` bytes.
+    ///
+    /// Every chunk but the last is exactly `chunk_size` bytes long, and together they make up the
+    /// same encoding as [`ToBencode::to_bencode`]. Each chunk is only produced when the iterator
+    /// is advanced, so a sender can wait for flow control between chunks without first buffering
+    /// the whole encoding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    ///
+    /// ```
+    /// # use bendy::value::Value;
+    /// #
+    /// let value = Value::List(vec![Value::Integer(1), Value::Bytes(b"foo".to_vec().into())]);
+    /// let chunks: Vec<_> = value.encode_chunks(4).collect();
+    ///
+    /// assert_eq!(chunks, [&b"li1e"[..], b"3:fo", b"oe"]);
+    /// ```
+    pub fn encode_chunks(&self, chunk_size: usize) -> EncodeChunks<'_, 'a> {
+        assert!(chunk_size > 0, "The chunk size must not be zero");
+
+        EncodeChunks {
+            chunk_size,
+            next_value: Some(self),
+            stack: Vec::new(),
+            header: Vec::new(),
+            header_pos: 0,
+            content: &[],
+        }
+    }
+}
+
+/// Iterator over the encoding of a [`Value`] in chunks, see [`Value::encode_chunks`].
+#[derive(Debug)]
+pub struct EncodeChunks<'v, 'a> {
+    chunk_size: usize,
+    /// The value to be encoded next, if it was already taken from its container
+    next_value: Option<&'v Value<'a>>,
+    /// The lists and dicts that are currently being encoded, innermost last
+    stack: Vec<ChunkFrame<'v, 'a>>,
+    /// The encoded framing of the current token, i.e. everything but the content of a byte string
+    header: Vec<u8>,
+    header_pos: usize,
+    /// The remaining content of the current byte string, which is copied from the value
+    content: &'v [u8],
+}
+
+#[derive(Debug)]
+enum ChunkFrame<'v, 'a> {
+    List(slice::Iter<'v, Value<'a>>),
+    Dict(btree_map::Iter<'v, Cow<'a, [u8]>, Value<'a>>),
+}
+
+impl<'v, 'a> EncodeChunks<'v, 'a> {
+    /// Queue the next token for output, returning `false` once the whole value has been encoded
+    fn advance(&mut self) -> bool {
+        self.header.clear();
+        self.header_pos = 0;
+
+        let value = match self.next_value.take() {
+            Some(value) => value,
+            None => match self.stack.last_mut() {
+                None => return false,
+                Some(ChunkFrame::List(items)) => match items.next() {
+                    Some(item) => item,
+                    None => return self.end_container(),
+                },
+                Some(ChunkFrame::Dict(entries)) => match entries.next() {
+                    Some((key, value)) => {
+                        // The value follows once its key has been output
+                        self.next_value = Some(value);
+                        self.queue_bytes(key);
+                        return true;
+                    },
+                    None => return self.end_container(),
+                },
+            },
+        };
+
+        self.queue_value(value);
+        true
+    }
+
+    fn end_container(&mut self) -> bool {
+        self.stack.pop();
+        self.header.push(b'e');
+        true
+    }
+
+    fn queue_value(&mut self, value: &'v Value<'a>) {
+        match value {
+            Value::Bytes(bytes) => self.queue_bytes(bytes),
+            Value::Dict(dict) => {
+                self.header.push(b'd');
+                self.stack.push(ChunkFrame::Dict(dict.iter()));
+            },
+            Value::Integer(integer) => {
+                self.header.push(b'i');
+                self.header
+                    .extend_from_slice(integer.to_string().as_bytes());
+                self.header.push(b'e');
+            },
+            Value::List(list) => {
+                self.header.push(b'l');
+                self.stack.push(ChunkFrame::List(list.iter()));
+            },
+        }
+    }
+
+    fn queue_bytes(&mut self, bytes: &'v [u8]) {
+        self.header
+            .extend_from_slice(bytes.len().to_string().as_bytes());
+        self.header.push(b':');
+        self.content = bytes;
+    }
+}
+
+impl<'v, 'a> Iterator for EncodeChunks<'v, 'a> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        let mut chunk = Vec::with_capacity(self.chunk_size);
+
+        while chunk.len() < self.chunk_size {
+            let space = self.chunk_size - chunk.len();
+            if self.header_pos < self.header.len() {
+                let header = &self.header[self.header_pos..];
+                let length = header.len().min(space);
+                chunk.extend_from_slice(&header[..length]);
+                self.header_pos += length;
+            } else if !self.content.is_empty() {
+                let length = self.content.len().min(space);
+                chunk.extend_from_slice(&self.content[..length]);
+                self.content = &self.content[length..];
+            } else if !self.advance() {
+                break;
+            }
+        }
+
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+}
+
+/// Fluent construction of [`Value`]s, e.g. for structures that are assembled in a loop.
+///
+/// ```
+/// # use bendy::{encoding::ToBencode, value::{Value, ValueBuilder}};
+/// #
+/// let mut files = ValueBuilder::list();
+/// for length in &[3, 5] {
+///     files = files.item(Value::Integer(*length));
+/// }
+///
+/// let value = ValueBuilder::dict()
+///     .entry("name", Value::Bytes(b"foo".to_vec().into()))
+///     .entry("lengths", files.build())
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(value.to_bencode().unwrap(), b"d7:lengthsli3ei5ee4:name3:fooe");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ValueBuilder;
+
+impl ValueBuilder {
+    /// Start building a dict. Entries may be added in any order.
+    pub fn dict<'a>() -> DictBuilder<'a> {
+        DictBuilder {
+            entries: BTreeMap::new(),
+            duplicate: None,
+        }
+    }
+
+    /// Start building a list.
+    pub fn list<'a>() -> ListBuilder<'a> {
+        ListBuilder { items: Vec::new() }
+    }
+}
+
+/// Builds a [`Value::Dict`], see [`ValueBuilder::dict`].
+#[derive(Clone, Debug)]
+pub struct DictBuilder<'a> {
+    entries: BTreeMap<Cow<'a, [u8]>, Value<'a>>,
+    duplicate: Option<Vec<u8>>,
+}
+
+impl<'a> DictBuilder<'a> {
+    /// Add an entry. The key may be anything that can be viewed as a byte string.
+    pub fn entry<K: AsRef<[u8]>>(mut self, key: K, value: Value<'a>) -> Self {
+        let key = key.as_ref();
+        if self.entries.contains_key(key) {
+            self.duplicate.get_or_insert_with(|| key.to_vec());
+        } else {
+            self.entries.insert(Cow::Owned(key.to_vec()), value);
+        }
+        self
+    }
+
+    /// Finish the dict. Fails if any key was added more than once.
+    pub fn build(self) -> Result<Value<'a>, encoding::Error> {
+        match self.duplicate {
+            Some(key) => Err(encoding::Error::from(StructureError::InvalidState {
+                state: format!("Duplicate key {}", String::from_utf8_lossy(&key)),
+            })),
+            None => Ok(Value::Dict(self.entries)),
+        }
+    }
+}
+
+/// Builds a [`Value::List`], see [`ValueBuilder::list`].
+#[derive(Clone, Debug)]
+pub struct ListBuilder<'a> {
+    items: Vec<Value<'a>>,
+}
+
+impl<'a> ListBuilder<'a> {
+    /// Append an item.
+    pub fn item(mut self, value: Value<'a>) -> Self {
+        self.items.push(value);
+        self
+    }
+
+    /// Finish the list.
+    pub fn build(self) -> Value<'a> {
+        Value::List(self.items)
+    }
 }
 
 impl<'a> ToBencode for Value<'a> {
@@ -216,6 +1217,178 @@ mod serde_impls {
     }
 }
 
+#[cfg(feature = "json")]
+pub use self::json_impls::ConversionError;
+
+#[cfg(feature = "json")]
+mod json_impls {
+    use super::*;
+
+    use serde_json::{Map, Number, Value as JsonValue};
+
+    /// The reason a JSON value can't be converted to a [`Value`].
+    #[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+    pub enum ConversionError {
+        /// Bencode has no representation for `null`.
+        #[snafu(display("JSON null has no bencode representation"))]
+        Null,
+
+        /// Bencode only supports integers, and `Value` only those in the range of `i64`.
+        #[snafu(display("JSON number {} is not an integer in the range of i64", number))]
+        UnsupportedNumber { number: Number },
+    }
+
+    impl Value<'static> {
+        /// Convert a JSON value to a bencode value.
+        ///
+        /// Objects become dicts, arrays become lists, strings become byte strings holding
+        /// their UTF-8 encoding and booleans become the integers `0` and `1`. Numbers must be
+        /// integers in the range of `i64`, and `null` can't be converted at all.
+        ///
+        /// ```
+        /// # use bendy::{encoding::ToBencode, value::Value};
+        /// #
+        /// let json = serde_json::json!({ "name": "foo", "private": true, "files": [1, 2] });
+        /// let value = Value::from_json(&json).unwrap();
+        ///
+        /// assert_eq!(
+        ///     value.to_bencode().unwrap(),
+        ///     &b"d5:filesli1ei2ee4:name3:foo7:privatei1ee"[..]
+        /// );
+        /// assert!(Value::from_json(&serde_json::json!(1.5)).is_err());
+        /// ```
+        pub fn from_json(json: &JsonValue) -> Result<Self, ConversionError> {
+            match json {
+                JsonValue::Null => Err(ConversionError::Null),
+                JsonValue::Bool(boolean) => Ok(Value::Integer(i64::from(*boolean))),
+                JsonValue::Number(number) => number.as_i64().map(Value::Integer).ok_or_else(|| {
+                    ConversionError::UnsupportedNumber {
+                        number: number.clone(),
+                    }
+                }),
+                JsonValue::String(string) => {
+                    Ok(Value::Bytes(Cow::Owned(string.as_bytes().to_vec())))
+                },
+                JsonValue::Array(array) => array
+                    .iter()
+                    .map(Value::from_json)
+                    .collect::<Result<_, _>>()
+                    .map(Value::List),
+                JsonValue::Object(object) => object
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = Cow::Owned(key.as_bytes().to_vec());
+                        Value::from_json(value).map(|value| (key, value))
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Value::Dict),
+            }
+        }
+    }
+
+    impl<'a> Value<'a> {
+        /// Convert this value to a JSON value.
+        ///
+        /// Dicts become objects, lists become arrays and integers become numbers. Byte strings
+        /// and dict keys that are valid UTF-8 become strings; any others are encoded as
+        /// standard, padded base64, which can't be told apart from a string with the same
+        /// content. Converting the result back with [`Value::from_json`] therefore only
+        /// restores the original value if all of its byte strings are valid UTF-8.
+        ///
+        /// ```
+        /// # use bendy::{decoding::FromBencode, value::Value};
+        /// #
+        /// let value = Value::from_bencode(b"d4:name3:foo6:pieces2:\xff\x00e").unwrap();
+        ///
+        /// assert_eq!(
+        ///     value.to_json(),
+        ///     serde_json::json!({ "name": "foo", "pieces": "/wA=" })
+        /// );
+        /// ```
+        pub fn to_json(&self) -> JsonValue {
+            match self {
+                Value::Bytes(bytes) => JsonValue::String(bytes_to_json_string(bytes)),
+                Value::Dict(dict) => JsonValue::Object(
+                    dict.iter()
+                        .map(|(key, value)| (bytes_to_json_string(key), value.to_json()))
+                        .collect::<Map<_, _>>(),
+                ),
+                Value::Integer(integer) => JsonValue::from(*integer),
+                Value::List(list) => JsonValue::Array(list.iter().map(Value::to_json).collect()),
+            }
+        }
+    }
+
+    fn bytes_to_json_string(bytes: &[u8]) -> String {
+        match core::str::from_utf8(bytes) {
+            Ok(string) => string.to_owned(),
+            Err(_) => base64(bytes),
+        }
+    }
+
+    fn base64(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+        for chunk in bytes.chunks(3) {
+            let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+                group | u32::from(byte) << (16 - 8 * i)
+            });
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+        encoded
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use serde_json::json;
+
+        #[test]
+        fn json_should_round_trip() {
+            let json = json!({ "a": [1, -2, "three", { "four": [] }], "b": {} });
+            assert_eq!(Value::from_json(&json).unwrap().to_json(), json);
+        }
+
+        #[test]
+        fn json_booleans_should_become_integers() {
+            assert_eq!(Value::from_json(&json!(false)).unwrap(), Value::Integer(0));
+            assert_eq!(
+                Value::from_json(&json!([true])).unwrap().to_json(),
+                json!([1])
+            );
+        }
+
+        #[test]
+        fn unsupported_json_should_be_rejected() {
+            assert_eq!(Value::from_json(&json!([null])), Err(ConversionError::Null));
+            assert!(Value::from_json(&json!(0.5)).is_err());
+            assert!(Value::from_json(&json!(u64::max_value())).is_err());
+        }
+
+        #[test]
+        fn non_utf8_bytes_should_become_base64() {
+            assert_eq!(base64(b""), "");
+            assert_eq!(base64(b"f"), "Zg==");
+            assert_eq!(base64(b"fo"), "Zm8=");
+            assert_eq!(base64(b"foo"), "Zm9v");
+            assert_eq!(base64(b"foob"), "Zm9vYg==");
+
+            let mut dict = BTreeMap::new();
+            dict.insert(Cow::Borrowed(&b"\xfb\xff"[..]), Value::Integer(1));
+            assert_eq!(Value::Dict(dict).to_json(), json!({ "+/8=": 1 }));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -256,7 +1429,7 @@ mod tests {
                 Ok(deserialized) => deserialized,
                 Err(err) => panic!(
                     "Failed to deserialize value from `{}`: {}",
-                    String::from_utf8_lossy(&expected),
+                    String::from_utf8_lossy(expected),
                     err
                 ),
             };
@@ -299,6 +1472,317 @@ mod tests {
         case(Value::Dict(dict), "d3:bari2e3:fooi1ee");
     }
 
+    #[test]
+    fn fold_should_visit_in_pre_order() {
+        let value = Value::from_bencode(b"d3:barli1ee3:fooi2ee").unwrap();
+        let visited = value.fold(Vec::new(), |mut visited, value| {
+            visited.push(value.to_bencode().unwrap());
+            visited
+        });
+
+        let expected: [&[u8]; 6] = [
+            b"d3:barli1ee3:fooi2ee",
+            b"3:bar",
+            b"li1ee",
+            b"i1e",
+            b"3:foo",
+            b"i2e",
+        ];
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn to_owned_value_should_outlive_source() {
+        let source = b"foobar".to_vec();
+        let borrowed = Value::List(vec![
+            Value::Dict(
+                vec![(
+                    Cow::Borrowed(&source[..3]),
+                    Value::Bytes(Cow::Borrowed(&source[3..])),
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            Value::Integer(1),
+        ]);
+
+        let owned = borrowed.to_owned_value();
+        assert_eq!(owned, borrowed);
+        drop(borrowed);
+        drop(source);
+        assert_eq!(owned.to_bencode().unwrap(), b"ld3:foo3:barei1ee");
+    }
+
+    #[test]
+    fn dict_entries_should_iterate_in_key_order() {
+        let value = Value::from_bencode(b"d1:ai1e1:bli2ee1:c0:e").unwrap();
+
+        assert_eq!(value.keys().collect::<Vec<_>>(), [b"a", b"b", b"c"]);
+        assert_eq!(
+            value.values().collect::<Vec<_>>(),
+            [
+                &Value::Integer(1),
+                &Value::List(vec![Value::Integer(2)]),
+                &Value::Bytes(Cow::Borrowed(b"")),
+            ]
+        );
+        assert_eq!(Value::List(vec![]).keys().count(), 0);
+        assert_eq!(Value::Bytes(Cow::Borrowed(b"abc")).values().count(), 0);
+    }
+
+    #[test]
+    fn encode_chunks_should_match_to_bencode() {
+        let value = Value::from_bencode(
+            b"d3:bar0:3:fooli1ei-23eld0:leeee4:quuxd1:a11:hello world1:bi1234567890eee",
+        )
+        .unwrap();
+        let encoded = value.to_bencode().unwrap();
+
+        for chunk_size in 1..=encoded.len() + 1 {
+            let chunks: Vec<_> = value.encode_chunks(chunk_size).collect();
+
+            assert_eq!(chunks.concat(), encoded, "chunk size {}", chunk_size);
+            let (last, full) = chunks.split_last().unwrap();
+            assert!(full.iter().all(|chunk| chunk.len() == chunk_size));
+            assert!(!last.is_empty() && last.len() <= chunk_size);
+        }
+    }
+
+    #[test]
+    fn encode_chunks_should_handle_atoms() {
+        let chunks: Vec<_> = Value::Integer(-7).encode_chunks(2).collect();
+        assert_eq!(chunks, [&b"i-"[..], b"7e"]);
+
+        let empty = Value::Bytes(Cow::Borrowed(b""));
+        assert_eq!(empty.encode_chunks(8).collect::<Vec<_>>(), [b"0:"]);
+    }
+
+    #[test]
+    fn builder_should_sort_dict_entries() {
+        let value = ValueBuilder::dict()
+            .entry(b"b", Value::Integer(2))
+            .entry("a", ValueBuilder::list().item(Value::Integer(1)).build())
+            .build()
+            .unwrap();
+
+        case(value, "d1:ali1ee1:bi2ee");
+    }
+
+    #[test]
+    fn builder_should_reject_duplicate_keys() {
+        let error = ValueBuilder::dict()
+            .entry(b"a", Value::Integer(1))
+            .entry(b"b", Value::Integer(2))
+            .entry(b"a", Value::Integer(3))
+            .build()
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            encoding::Error::from(StructureError::invalid_state("Duplicate key a"))
+        );
+    }
+
+    #[test]
+    fn flattened_values_should_unflatten_to_the_original() {
+        let encoded = b"d0:d0:i0e1:ai1ee3:%.[d1:]le1:\xffi2ee4:listli3ed1:ai4eeli5eeee";
+        let value = Value::from_bencode(encoded).unwrap();
+        let flat = value.flatten();
+
+        let paths: Vec<_> = flat.keys().map(String::as_str).collect();
+        let expected = [
+            "%25%2E%5B.%FF",
+            "%25%2E%5B.]",
+            "..",
+            "..a",
+            "list[0]",
+            "list[1].a",
+        ];
+        assert_eq!(paths[..6], expected);
+        assert_eq!(paths[6..], ["list[2][0]"]);
+        assert_eq!(flat["%25%2E%5B.]"], Value::List(vec![]));
+        assert_eq!(Value::unflatten(flat).unwrap(), value);
+
+        let list = Value::from_bencode(b"ld0:i1eee").unwrap();
+        let flat = list.flatten();
+        assert_eq!(flat.keys().collect::<Vec<_>>(), ["[0]."]);
+        assert_eq!(Value::unflatten(flat).unwrap(), list);
+
+        for leaf in &[Value::Integer(1), Value::Dict(BTreeMap::new())] {
+            let flat = leaf.flatten();
+            assert_eq!(flat.keys().collect::<Vec<_>>(), [""]);
+            assert_eq!(&Value::unflatten(flat).unwrap(), leaf);
+        }
+    }
+
+    #[test]
+    fn conflicting_flat_paths_should_be_rejected() {
+        let unflatten = |paths: &[&str]| {
+            let entries = paths
+                .iter()
+                .map(|path| (path.to_string(), Value::Integer(0)))
+                .collect();
+            Value::unflatten(entries)
+        };
+        let invalid = |path: &str| UnflattenError::InvalidPath { path: path.into() };
+        let conflict = |path: &str| UnflattenError::Conflict { path: path.into() };
+
+        assert_eq!(unflatten(&["a", "a.b"]), Err(conflict("a.b")));
+        assert_eq!(unflatten(&["a.b", "a[0]"]), Err(conflict("a[0]")));
+        assert_eq!(unflatten(&["", "a"]), Err(conflict("a")));
+        assert_eq!(unflatten(&["", "."]), Err(conflict(".")));
+        for path in &[".a", "a%2", "a%zz", "[01]", "[]", "[1", "[-1]", "[0]a"] {
+            assert_eq!(unflatten(&[*path]), Err(invalid(path)));
+        }
+        assert_eq!(
+            unflatten(&["a[1]"]),
+            Err(UnflattenError::MissingElement {
+                path: "a[0]".into()
+            })
+        );
+        assert_eq!(unflatten(&[]), Ok(Value::Dict(BTreeMap::new())));
+    }
+
+    #[test]
+    fn values_should_be_canonical_however_they_are_built() {
+        let mut dict = BTreeMap::new();
+        dict.insert(Cow::Borrowed(&b"b\xff"[..]), Value::Integer(1));
+        dict.insert(Cow::Owned(b"b".to_vec()), Value::List(vec![]));
+        dict.insert(Cow::Borrowed(&b""[..]), Value::Dict(BTreeMap::new()));
+        let value = Value::List(vec![Value::Dict(dict), Value::Bytes(b"x"[..].into())]);
+
+        assert!(value.is_canonical());
+        let encoded = value.to_bencode().unwrap();
+        assert_eq!(encoded, b"ld0:de1:ble2:b\xffi1ee1:xe");
+        assert_eq!(Value::from_bencode(&encoded).unwrap(), value);
+    }
+
+    #[test]
+    fn projection_should_keep_only_listed_top_level_keys() {
+        let value = Value::from_bencode(b"d1:ad1:bi1ee1:bi2e1:ci3ee").unwrap();
+
+        case(
+            value.project(&[b"c", b"a", b"b", b"x"]),
+            "d1:ad1:bi1ee1:bi2e1:ci3ee",
+        );
+        case(value.project(&[b"c", b"b", b"c"]), "d1:bi2e1:ci3ee");
+        case(value.project(&[]), "de");
+        case(Value::Integer(1).project(&[b"a"]), "de");
+    }
+
+    #[test]
+    fn patches_should_edit_lists_and_dicts() {
+        use self::PathSegment::*;
+
+        let mut value = Value::List(vec![Value::Integer(1), Value::Integer(3)]);
+        value
+            .apply_patch(&[
+                (vec![Index(1)], PatchOp::Insert(Value::Integer(2))),
+                (
+                    vec![Index(3)],
+                    PatchOp::Insert(Value::Dict(BTreeMap::new())),
+                ),
+                (vec![Index(0)], PatchOp::Remove),
+                (vec![Index(0)], PatchOp::Set(Value::Integer(0))),
+                (vec![Index(2), Key(b"a")], PatchOp::Set(Value::Integer(4))),
+            ])
+            .unwrap();
+        case(value.clone(), "li0ei3ed1:ai4eee");
+
+        value
+            .apply_patch(&[(vec![], PatchOp::Set(Value::Integer(1)))])
+            .unwrap();
+        assert_eq!(value, Value::Integer(1));
+    }
+
+    #[test]
+    fn patch_errors_should_name_the_failed_entry() {
+        use self::PathSegment::*;
+
+        let mut value = Value::List(vec![Value::Integer(1)]);
+        let error = value
+            .apply_patch(&[
+                (vec![Index(1)], PatchOp::Insert(Value::Integer(2))),
+                (vec![Index(3)], PatchOp::Set(Value::Integer(3))),
+            ])
+            .unwrap_err();
+        assert_eq!(error.entry(), 1);
+        assert_eq!(error.to_string(), "patch entry 1: no value at [3]");
+        // earlier entries stay applied
+        case(value.clone(), "li1ei2ee");
+
+        let error = value
+            .apply_patch(&[(vec![Index(0), Key(b"a")], PatchOp::Remove)])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "patch entry 0: the value at [0] is not a dict"
+        );
+
+        let error = value
+            .apply_patch(&[(vec![Key(b"a")], PatchOp::Remove)])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "patch entry 0: the value at / is not a dict"
+        );
+
+        let error = value
+            .apply_patch(&[(vec![Index(5), Index(0)], PatchOp::Remove)])
+            .unwrap_err();
+        assert_eq!(error.to_string(), "patch entry 0: no value at [5]");
+
+        let error = value.apply_patch(&[(vec![], PatchOp::Remove)]).unwrap_err();
+        assert_eq!(error, PatchError::InvalidRootOperation { entry: 0 });
+
+        let mut value = ValueBuilder::dict()
+            .entry(b"a", Value::Integer(1))
+            .build()
+            .unwrap();
+        let error = value
+            .apply_patch(&[(vec![Key(b"a")], PatchOp::Insert(Value::Integer(2)))])
+            .unwrap_err();
+        assert_eq!(error.to_string(), "patch entry 0: /a already exists");
+    }
+
+    #[test]
+    fn homogeneous_lists() {
+        assert_eq!(Value::List(Vec::new()).as_integer_list(), Some(Vec::new()));
+        assert_eq!(Value::List(Vec::new()).as_bytes_list(), Some(Vec::new()));
+        assert_eq!(Value::Integer(1).as_integer_list(), None);
+        assert_eq!(Value::Bytes(Cow::Borrowed(b"a")).as_bytes_list(), None);
+    }
+
+    #[test]
+    fn describe_path() {
+        let path = [PathSegment::Index(0), PathSegment::Key(b"a\xffb")];
+        assert_eq!(Value::describe_path(&path), "[0]/a\u{fffd}b");
+    }
+
+    #[test]
+    fn from_bencode_limited() {
+        let nested = b"ld3:fooli1eee3:bare";
+        assert!(Value::from_bencode_limited(nested, 3, 3).is_ok());
+        assert!(Value::from_bencode_limited(nested, 2, 3).is_err());
+        assert!(Value::from_bencode_limited(nested, 3, 2).is_err());
+        assert!(Value::from_bencode_limited(b"", 3, 3).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_is_consistent_with_eq() {
+        use std::collections::HashSet;
+
+        let borrowed = Value::from_bencode(b"d3:bar3:baz3:fooli1eee").unwrap();
+        let owned = borrowed.clone().into_owned();
+
+        let mut set = HashSet::new();
+        set.insert(borrowed);
+        assert!(!set.insert(owned));
+        assert!(set.insert(Value::from_bencode(b"d3:bar3:baz3:fooli2eee").unwrap()));
+        assert_eq!(set.len(), 2);
+    }
+
     #[test]
     fn integer() {
         case(Value::Integer(0), "i0e");
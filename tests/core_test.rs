@@ -18,12 +18,7 @@ use bendy::{
 macro_rules! list(
     {} => { Vec::<Something>::new() };
     { $($value:expr),+ } => {
-        {
-            let mut list = Vec::new();
-            $( list.push(Something::from($value)); )+
-
-            list
-        }
+        vec![$( Something::from($value) ),+]
      };
 );
 
@@ -64,6 +59,7 @@ fn string_test_pairs() -> Result<(), Error> {
 }
 
 #[test]
+#[allow(clippy::zero_prefixed_literal)]
 fn integer_test_pairs() -> Result<(), Error> {
     let pairs = [
         (0, "i0e"),
@@ -260,6 +256,24 @@ fn illegal_integer_encodings() {
     }
 }
 
+#[test]
+fn illegal_integer_encodings_name_the_broken_rule() {
+    let cases = [
+        ("i-0e", "negative zero is not allowed"),
+        ("i0123e", "leading zeros are not allowed"),
+        ("i-0123e", "leading zeros are not allowed"),
+        ("i-0xe", "negative zero is not allowed"),
+        ("i-e", "a minus sign must be followed by digits"),
+        ("ie", "at least one digit is required"),
+    ];
+
+    for (value, rule) in &cases {
+        let error = i64::from_bencode(value.as_bytes()).unwrap_err().to_string();
+        assert!(error.contains("encoding corrupted"), "{}: {}", value, error);
+        assert!(error.contains(rule), "{}: {}", value, error);
+    }
+}
+
 #[test]
 fn illegal_string_encodings() {
     let values = [":hello", "-5:hello", "-5:", "5:", "10:hello"];
@@ -409,6 +423,7 @@ impl ToBencode for Something {
 // Error
 // -----------------------------------------------------------------------------
 
+#[allow(dead_code)] // only read through the `Debug` output
 #[derive(Debug)]
 enum Error {
     DecodingError(DecodingError),
@@ -14,7 +14,7 @@ impl ToBencode for Example {
 
     fn encode(&self, encoder: SingleItemEncoder) -> Result<(), EncodingError> {
         encoder.emit_dict(|mut dict| {
-            dict.emit_pair(b"bar", &self.bar)?;
+            dict.emit_pair(b"bar", self.bar)?;
             dict.emit_pair(b"foo", &self.foo)
         })
     }